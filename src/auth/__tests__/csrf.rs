@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use crate::auth::csrf::{issue, matches, verify};
+
+    #[test]
+    fn should_verify_signed_tokens_only() {
+        let token = issue("secret", None);
+        let nonce = token.split('.').nth(1).unwrap();
+
+        assert_eq!(verify(&token, "secret"), Some(String::new()));
+        assert_eq!(verify(&token, "other secret"), None);
+        assert_eq!(verify(&format!(".{}", nonce), "secret"), None);
+        assert_eq!(verify(&format!(".{}.00", nonce), "secret"), None);
+    }
+
+    #[test]
+    fn should_bind_tokens_to_session() {
+        let token = issue("secret", Some("session"));
+        let forged = token.replacen("session", "other", 1);
+
+        assert_eq!(verify(&token, "secret"), Some("session".to_string()));
+        assert_eq!(verify(&forged, "secret"), None);
+    }
+
+    #[test]
+    fn should_match_identical_tokens() {
+        let token = issue("secret", None);
+
+        assert!(matches(&token, &token));
+        assert!(!matches(&token, &issue("secret", None)));
+        assert!(!matches(&token, &token[1..]));
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{
+            header::{AUTHORIZATION, COOKIE},
+            HeaderMap, HeaderValue, StatusCode,
+        },
+        response::IntoResponse,
+    };
+    use bson::oid::ObjectId;
+    use chrono::Utc;
+
+    use crate::{
+        auth::guard::{extract_token, AuthUserOrPublic, TokenSource},
+        constants::auth::TOKEN_COOKIE_KEY,
+        models::{
+            api_token::TokenScope,
+            user::{User, UserRole},
+        },
+    };
+
+    const COOKIE_FIRST: &[TokenSource] = &[TokenSource::Cookie, TokenSource::Bearer];
+    const BEARER_FIRST: &[TokenSource] = &[TokenSource::Bearer, TokenSource::Cookie];
+
+    fn headers(bearer: Option<&str>, cookie: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        if let Some(bearer) = bearer {
+            headers.insert(AUTHORIZATION, HeaderValue::from_str(bearer).unwrap());
+        }
+        if let Some(cookie) = cookie {
+            headers.insert(
+                COOKIE,
+                HeaderValue::from_str(&format!("{}={}", TOKEN_COOKIE_KEY, cookie)).unwrap(),
+            );
+        }
+
+        headers
+    }
+
+    #[test]
+    fn should_extract_bearer_token_only() {
+        let headers = headers(Some("Bearer header-token"), None);
+
+        assert_eq!(
+            extract_token(&headers, COOKIE_FIRST),
+            Some((TokenSource::Bearer, "header-token".to_string()))
+        );
+        assert_eq!(extract_token(&headers, &[TokenSource::Cookie]), None);
+    }
+
+    #[test]
+    fn should_extract_cookie_token_only() {
+        let headers = headers(None, Some("cookie-token"));
+
+        assert_eq!(
+            extract_token(&headers, BEARER_FIRST),
+            Some((TokenSource::Cookie, "cookie-token".to_string()))
+        );
+        assert_eq!(extract_token(&headers, &[TokenSource::Bearer]), None);
+    }
+
+    #[test]
+    fn should_follow_precedence_when_both_are_sent() {
+        let headers = headers(Some("Bearer header-token"), Some("cookie-token"));
+
+        assert_eq!(
+            extract_token(&headers, COOKIE_FIRST),
+            Some((TokenSource::Cookie, "cookie-token".to_string()))
+        );
+        assert_eq!(
+            extract_token(&headers, BEARER_FIRST),
+            Some((TokenSource::Bearer, "header-token".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_ignore_malformed_authorization_header() {
+        for value in ["header-token", "Basic dXNlcjpwYXNz", "Bearer ", "Bearer"] {
+            let malformed = headers(Some(value), None);
+
+            assert_eq!(extract_token(&malformed, BEARER_FIRST), None);
+        }
+
+        // The cookie is still used when the header is malformed
+        let headers = headers(Some("Token header-token"), Some("cookie-token"));
+
+        assert_eq!(
+            extract_token(&headers, BEARER_FIRST),
+            Some((TokenSource::Cookie, "cookie-token".to_string()))
+        );
+    }
+
+    fn root(scopes: Option<Vec<TokenScope>>) -> AuthUserOrPublic {
+        AuthUserOrPublic {
+            user: Some(User {
+                id: Some(ObjectId::new()),
+                name: "root".to_string(),
+                email: None,
+                password: String::new(),
+                role: UserRole::Root,
+                sessions_valid_after: None,
+                totp: None,
+                disabled_at: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }),
+            scopes,
+        }
+    }
+
+    #[test]
+    fn should_forbid_root_token_without_write_scope() {
+        let rejection = root(Some(vec![TokenScope::CommentsRead]))
+            .require_scope(TokenScope::CommentsWrite)
+            .unwrap_err();
+
+        assert_eq!(rejection.into_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn should_allow_sessions_and_scoped_tokens() {
+        assert!(root(None).require_scope(TokenScope::CommentsWrite).is_ok());
+        assert!(root(Some(vec![TokenScope::CommentsWrite]))
+            .require_scope(TokenScope::CommentsWrite)
+            .is_ok());
+        assert!(AuthUserOrPublic {
+            user: None,
+            scopes: None,
+        }
+        .require_scope(TokenScope::CommentsWrite)
+        .is_ok());
+    }
+
+    #[test]
+    fn should_forbid_author_token_without_write_scope() {
+        let auth = root(Some(vec![TokenScope::CommentsRead]));
+        let author_id = auth.user.as_ref().and_then(|user| user.id);
+        let rejection = auth.is_author(author_id.as_ref()).unwrap_err();
+
+        assert_eq!(rejection.into_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn should_grant_author_rights_to_sessions_and_write_tokens() {
+        for scopes in [None, Some(vec![TokenScope::CommentsWrite])] {
+            let auth = root(scopes);
+            let author_id = auth.user.as_ref().and_then(|user| user.id);
+
+            assert!(auth.is_author(author_id.as_ref()).unwrap());
+            assert!(!auth.is_author(Some(&ObjectId::new())).unwrap());
+            assert!(!auth.is_author(None).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_not_reject_read_only_token_of_other_users() {
+        let auth = root(Some(vec![TokenScope::CommentsRead]));
+
+        assert!(!auth.is_author(Some(&ObjectId::new())).unwrap());
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests {
+    use crate::auth::magic_link::{issue, verify};
+
+    #[test]
+    fn should_verify_token_of_lowercased_email() {
+        let token = issue("Commenter@Example.com", "secret");
+        let link = verify(&token, "secret").unwrap();
+
+        assert_eq!(link.email, "commenter@example.com");
+        assert_ne!(
+            link.jti,
+            verify(&issue("commenter@example.com", "secret"), "secret")
+                .unwrap()
+                .jti
+        );
+        assert!(verify(&token, "other secret").is_none());
+    }
+
+    #[test]
+    fn should_reject_token_for_another_email() {
+        let token = issue("commenter@example.com", "secret");
+        let (signed, _) = token.rsplit_once('.').unwrap();
+        let forged = format!("{}.{}", signed, "YXR0YWNrZXJAZXhhbXBsZS5jb20");
+
+        assert!(verify(&forged, "secret").is_none());
+    }
+}
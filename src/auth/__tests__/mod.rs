@@ -0,0 +1,8 @@
+mod csrf;
+mod guard;
+mod indieauth;
+mod magic_link;
+mod oauth;
+mod passkey;
+mod permission;
+mod token;
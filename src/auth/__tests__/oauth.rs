@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use data_encoding::BASE64URL_NOPAD;
+    use serde_json::{json, Value};
+
+    use crate::auth::oauth::{code_challenge, OAuthProvider, ProviderKind};
+
+    const ISSUER: &str = "https://accounts.example.com";
+
+    #[test]
+    fn should_derive_s256_code_challenge() {
+        // RFC 7636 Appendix B
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    fn provider() -> OAuthProvider {
+        OAuthProvider::new(
+            "example",
+            ProviderKind::Oidc,
+            "client".to_string(),
+            "secret".to_string(),
+            Some(format!("{}/.well-known/openid-configuration", ISSUER)),
+        )
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": ISSUER,
+            "sub": "subject",
+            "aud": "client",
+            "exp": Utc::now().timestamp() + 60,
+            "nonce": "nonce",
+            "preferred_username": "someone",
+        })
+    }
+
+    fn id_token(claims: &Value) -> String {
+        format!(
+            "{}.{}.signature",
+            BASE64URL_NOPAD.encode(br#"{"alg":"RS256"}"#),
+            BASE64URL_NOPAD.encode(claims.to_string().as_bytes())
+        )
+    }
+
+    #[test]
+    fn should_accept_valid_id_token() {
+        let account = provider()
+            .verify_id_token(&id_token(&claims()), Some(ISSUER), "nonce")
+            .unwrap();
+
+        assert_eq!(account.subject, "subject");
+        assert_eq!(account.name, "someone");
+
+        let mut claims = claims();
+        claims["aud"] = json!(["other", "client"]);
+
+        assert!(provider()
+            .verify_id_token(&id_token(&claims), Some(ISSUER), "nonce")
+            .is_ok());
+    }
+
+    #[test]
+    fn should_reject_other_issuer() {
+        let mut claims = claims();
+        claims["iss"] = json!("https://attacker.example.com");
+
+        assert!(provider()
+            .verify_id_token(&id_token(&claims), Some(ISSUER), "nonce")
+            .is_err());
+    }
+
+    #[test]
+    fn should_reject_other_audience() {
+        let mut claims = claims();
+        claims["aud"] = json!(["other"]);
+
+        assert!(provider()
+            .verify_id_token(&id_token(&claims), Some(ISSUER), "nonce")
+            .is_err());
+    }
+
+    #[test]
+    fn should_reject_other_or_missing_nonce() {
+        assert!(provider()
+            .verify_id_token(&id_token(&claims()), Some(ISSUER), "other")
+            .is_err());
+
+        let mut claims = claims();
+        claims.as_object_mut().unwrap().remove("nonce");
+
+        assert!(provider()
+            .verify_id_token(&id_token(&claims), Some(ISSUER), "nonce")
+            .is_err());
+    }
+
+    #[test]
+    fn should_reject_expired_id_token() {
+        let mut claims = claims();
+        claims["exp"] = json!(Utc::now().timestamp() - 60);
+
+        assert!(provider()
+            .verify_id_token(&id_token(&claims), Some(ISSUER), "nonce")
+            .is_err());
+    }
+}
@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use bson::oid::ObjectId;
+    use webauthn_authenticator_rs::{softpasskey::SoftPasskey, WebauthnAuthenticator};
+    use webauthn_rs::prelude::{Passkey, PasskeyAuthentication, PasskeyRegistration, Url};
+
+    use crate::auth::passkey::{relying_party, user_handle};
+
+    #[test]
+    fn should_register_and_sign_in_with_software_authenticator() {
+        let webauthn = relying_party(".example.com", None).unwrap();
+        let origin = Url::parse("https://example.com").unwrap();
+        let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let (options, registration) = webauthn
+            .start_passkey_registration(user_handle(ObjectId::new()), "marshall", "marshall", None)
+            .unwrap();
+        // Ceremony states are stored as JSON between the two requests
+        let registration: PasskeyRegistration =
+            serde_json::from_str(&serde_json::to_string(&registration).unwrap()).unwrap();
+        let credential = authenticator
+            .do_registration(origin.clone(), options)
+            .unwrap();
+        let passkey = webauthn
+            .finish_passkey_registration(&credential, &registration)
+            .unwrap();
+
+        // Passkeys are stored in MongoDB
+        let passkey: Passkey = bson::from_document(bson::to_document(&passkey).unwrap()).unwrap();
+
+        let (options, authentication) = webauthn
+            .start_passkey_authentication(std::slice::from_ref(&passkey))
+            .unwrap();
+        let authentication: PasskeyAuthentication =
+            serde_json::from_str(&serde_json::to_string(&authentication).unwrap()).unwrap();
+        let credential = authenticator.do_authentication(origin, options).unwrap();
+        let result = webauthn
+            .finish_passkey_authentication(&credential, &authentication)
+            .unwrap();
+
+        assert_eq!(result.cred_id(), passkey.cred_id());
+    }
+}
@@ -0,0 +1,22 @@
+#[cfg(test)]
+mod tests {
+    use crate::{auth::permission::Permission, models::user::UserRole};
+
+    #[test]
+    fn should_grant_moderation_without_administration_to_moderators() {
+        assert!(Permission::CommentDelete.is_granted_to(&UserRole::Moderator));
+        assert!(Permission::BanManage.is_granted_to(&UserRole::Moderator));
+        assert!(!Permission::UserManage.is_granted_to(&UserRole::Moderator));
+        assert!(!Permission::TokenManage.is_granted_to(&UserRole::Moderator));
+
+        assert!(Permission::UserManage.is_granted_to(&UserRole::Root));
+        assert!(!Permission::CommentRead.is_granted_to(&UserRole::User));
+    }
+
+    #[test]
+    fn should_keep_administration_out_of_api_tokens() {
+        assert!(Permission::InviteManage.scope().is_none());
+        assert!(Permission::TokenManage.scope().is_none());
+        assert!(Permission::UserManage.scope().is_none());
+    }
+}
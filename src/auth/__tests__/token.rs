@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use data_encoding::BASE64URL_NOPAD;
+    use hmac::{Hmac, Mac};
+    use openssl::{
+        ec::{EcGroup, EcKey},
+        nid::Nid,
+        pkey::PKey,
+        rsa::Rsa,
+    };
+    use sha2::Sha256;
+
+    use crate::auth::{
+        keyring::{Algorithm, Key, Keyring},
+        token::{Token, TokenClaims, TokenConfig, TokenError},
+    };
+
+    fn config(signing_key: Key, previous: Vec<Key>) -> TokenConfig {
+        TokenConfig {
+            keyring: Keyring::new(signing_key, previous).unwrap(),
+            algorithms: vec![
+                Algorithm::Hs256,
+                Algorithm::Rs256,
+                Algorithm::Es256,
+                Algorithm::EdDsa,
+            ],
+            issuer: "issuer".to_string(),
+            audience: "audience".to_string(),
+        }
+    }
+
+    fn claims(audience: &str) -> TokenClaims {
+        let now = Utc::now().timestamp();
+
+        TokenClaims {
+            sub: "user".to_string(),
+            username: "name".to_string(),
+            iss: Some("issuer".to_string()),
+            aud: Some(audience.to_string()),
+            jti: "jti".to_string(),
+            sid: String::new(),
+            iat: now,
+            nbf: Some(now),
+            exp: now + 60,
+        }
+    }
+
+    #[test]
+    fn should_verify_tokens_of_previous_keys() {
+        let old = config(Key::from_secret("old"), vec![]);
+        let token = Token::sign(&claims("audience"), &old.keyring).unwrap();
+
+        let rotated = config(Key::from_secret("new"), vec![Key::from_secret("old")]);
+        assert_eq!(Token::parse(&token, &rotated).unwrap().sub, "user");
+
+        let config = config(Key::from_secret("new"), vec![]);
+        assert_eq!(
+            Token::parse(&token, &config).unwrap_err(),
+            TokenError::Signature
+        );
+    }
+
+    #[test]
+    fn should_reject_tokens_for_another_audience() {
+        let config = config(Key::from_secret("secret"), vec![]);
+        let token = Token::sign(&claims("other"), &config.keyring).unwrap();
+
+        assert_eq!(
+            Token::parse(&token, &config).unwrap_err(),
+            TokenError::Claims
+        );
+    }
+
+    #[test]
+    fn should_parse_legacy_tokens_with_string_dates() {
+        let now = Utc::now().timestamp();
+        let signing_input = format!(
+            "{}.{}",
+            BASE64URL_NOPAD.encode(br#"{"alg":"HS256"}"#),
+            BASE64URL_NOPAD.encode(
+                format!(
+                    r#"{{"exp":"{}","iat":"{}","sub":"user","username":"name"}}"#,
+                    now + 60,
+                    now
+                )
+                .as_bytes()
+            )
+        );
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(signing_input.as_bytes());
+        let token = format!(
+            "{}.{}",
+            signing_input,
+            BASE64URL_NOPAD.encode(&mac.finalize().into_bytes())
+        );
+
+        let claims = Token::parse(&token, &config(Key::from_secret("secret"), vec![])).unwrap();
+
+        assert_eq!(claims.exp, now + 60);
+        assert!(claims.iss.is_none());
+    }
+
+    #[test]
+    fn should_sign_with_asymmetric_keys() {
+        let ec_group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+        let pems = [
+            PKey::generate_ed25519().unwrap().private_key_to_pem_pkcs8(),
+            PKey::from_ec_key(EcKey::generate(&ec_group).unwrap())
+                .unwrap()
+                .private_key_to_pem_pkcs8(),
+            PKey::from_rsa(Rsa::generate(2048).unwrap())
+                .unwrap()
+                .private_key_to_pem_pkcs8(),
+        ];
+
+        for pem in pems {
+            let pem = pem.unwrap();
+            let config = config(Key::from_pem(&pem).unwrap(), vec![]);
+            let token = Token::sign(&claims("audience"), &config.keyring).unwrap();
+
+            assert_eq!(Token::parse(&token, &config).unwrap().sub, "user");
+
+            let jwk = config.keyring.signing_key().to_jwk().unwrap();
+            assert_eq!(jwk["kid"], config.keyring.signing_key().id);
+            assert!(jwk.get("d").is_none());
+        }
+    }
+
+    #[test]
+    fn should_reject_algorithms_outside_allow_list() {
+        let pem = PKey::generate_ed25519()
+            .unwrap()
+            .private_key_to_pem_pkcs8()
+            .unwrap();
+        let mut config = config(Key::from_pem(&pem).unwrap(), vec![]);
+        let token = Token::sign(&claims("audience"), &config.keyring).unwrap();
+
+        config.algorithms = vec![Algorithm::Hs256];
+
+        assert_eq!(
+            Token::parse(&token, &config).unwrap_err(),
+            TokenError::Algorithm
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::Utc;
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::constants::auth::TOTP_CHALLENGE_LIFETIME_IN_SECONDS;
+
+/// Issue a token proving the user passed the password step of a signin
+///
+/// The token has the form `<user id>.<unix expiry>.<hex signature>`, and
+/// can't be used as an access token.
+pub fn issue(user_id: &str, secret_key: &str) -> String {
+    let expires_at = Utc::now().timestamp() + TOTP_CHALLENGE_LIFETIME_IN_SECONDS;
+    let signature = new_mac(secret_key, user_id, expires_at)
+        .finalize()
+        .into_bytes();
+
+    format!("{}.{}.{}", user_id, expires_at, HEXLOWER.encode(&signature))
+}
+
+/// Verify a challenge token and return the id of its user
+///
+/// Returns `None` if the token is malformed, expired or the signature doesn't match.
+pub fn verify(token: &str, secret_key: &str) -> Option<String> {
+    let mut parts = token.splitn(3, '.');
+    let user_id = parts.next()?;
+    let expires_at = parts.next()?.parse::<i64>().ok()?;
+    let signature = HEXLOWER.decode(parts.next()?.as_bytes()).ok()?;
+
+    if expires_at < Utc::now().timestamp() {
+        return None;
+    }
+
+    new_mac(secret_key, user_id, expires_at)
+        .verify_slice(&signature)
+        .ok()
+        .map(|_| user_id.to_string())
+}
+
+fn new_mac(secret_key: &str, user_id: &str, expires_at: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(b"totp-challenge:");
+    mac.update(user_id.as_bytes());
+    mac.update(b":");
+    mac.update(expires_at.to_string().as_bytes());
+    mac
+}
@@ -0,0 +1,99 @@
+use cookie::{Cookie, SameSite};
+use time::Duration;
+
+use crate::{
+    constants::auth::{
+        ACCESS_TOKEN_LIFETIME_IN_SECONDS, CSRF_COOKIE_KEY, INDIEAUTH_STATE_COOKIE_KEY,
+        OAUTH_STATE_COOKIE_KEY, OAUTH_STATE_LIFETIME_IN_SECONDS, REFRESH_TOKEN_COOKIE_KEY,
+        REFRESH_TOKEN_LIFETIME_IN_SECONDS, TOKEN_COOKIE_KEY, VERIFIED_SITE_COOKIE_KEY,
+        VERIFIED_SITE_LIFETIME_IN_SECONDS,
+    },
+    controllers::app::API_VERSION_PREFIX,
+};
+
+pub fn access_token_cookie(token: String, domain: String) -> Cookie<'static> {
+    Cookie::build((TOKEN_COOKIE_KEY, token))
+        .path("/")
+        .secure(true)
+        .http_only(true)
+        .max_age(Duration::seconds(ACCESS_TOKEN_LIFETIME_IN_SECONDS))
+        .same_site(SameSite::None)
+        .domain(domain)
+        .build()
+}
+
+/// The refresh token is only sent to the auth endpoints
+pub fn refresh_token_cookie(token: String, domain: String) -> Cookie<'static> {
+    Cookie::build((REFRESH_TOKEN_COOKIE_KEY, token))
+        .path(format!("{}/auth", API_VERSION_PREFIX))
+        .secure(true)
+        .http_only(true)
+        .max_age(Duration::seconds(REFRESH_TOKEN_LIFETIME_IN_SECONDS))
+        .same_site(SameSite::None)
+        .domain(domain)
+        .build()
+}
+
+/// CSRF token, readable by scripts so they can echo it in a header
+pub fn csrf_cookie(token: String, domain: String) -> Cookie<'static> {
+    Cookie::build((CSRF_COOKIE_KEY, token))
+        .path("/")
+        .secure(true)
+        .max_age(Duration::seconds(REFRESH_TOKEN_LIFETIME_IN_SECONDS))
+        .same_site(SameSite::None)
+        .domain(domain)
+        .build()
+}
+
+/// State of an OAuth sign in, only sent back to the callback
+///
+/// `Lax` so it comes along when the provider redirects back.
+pub fn oauth_state_cookie(state: String, domain: String) -> Cookie<'static> {
+    Cookie::build((OAUTH_STATE_COOKIE_KEY, state))
+        .path(format!("{}/auth/oauth", API_VERSION_PREFIX))
+        .secure(true)
+        .http_only(true)
+        .max_age(Duration::seconds(OAUTH_STATE_LIFETIME_IN_SECONDS))
+        .same_site(SameSite::Lax)
+        .domain(domain)
+        .build()
+}
+
+/// State of an IndieAuth verification, only sent back to the callback
+///
+/// `Lax` so it comes along when the authorization server redirects back.
+pub fn indieauth_state_cookie(state: String, domain: String) -> Cookie<'static> {
+    Cookie::build((INDIEAUTH_STATE_COOKIE_KEY, state))
+        .path(format!("{}/auth/indieauth", API_VERSION_PREFIX))
+        .secure(true)
+        .http_only(true)
+        .max_age(Duration::seconds(OAUTH_STATE_LIFETIME_IN_SECONDS))
+        .same_site(SameSite::Lax)
+        .domain(domain)
+        .build()
+}
+
+/// Proof of the website verified through IndieAuth, sent with new comments
+pub fn verified_site_cookie(proof: String, domain: String) -> Cookie<'static> {
+    Cookie::build((VERIFIED_SITE_COOKIE_KEY, proof))
+        .path("/")
+        .secure(true)
+        .http_only(true)
+        .max_age(Duration::seconds(VERIFIED_SITE_LIFETIME_IN_SECONDS))
+        .same_site(SameSite::None)
+        .domain(domain)
+        .build()
+}
+
+/// Cookies removing the access, refresh and CSRF tokens from the browser
+pub fn removal_cookies(domain: String) -> [Cookie<'static>; 3] {
+    let mut access_token = access_token_cookie(String::new(), domain.clone());
+    let mut refresh_token = refresh_token_cookie(String::new(), domain.clone());
+    let mut csrf_token = csrf_cookie(String::new(), domain);
+
+    access_token.make_removal();
+    refresh_token.make_removal();
+    csrf_token.make_removal();
+
+    [access_token, refresh_token, csrf_token]
+}
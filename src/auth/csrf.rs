@@ -0,0 +1,57 @@
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::utils::encryption::generate_token;
+
+/// Issue a token for the double-submit check of the CSRF middleware, bound
+/// to the session it's issued for
+///
+/// The token has the form `<session id>.<nonce>.<hex signature>`, the
+/// session id being empty for visitors who aren't signed in. As the
+/// middleware checks the session against the one of the request, a token
+/// planted by a sibling subdomain can't pass for a signed in user's.
+pub fn issue(secret_key: &str, session_id: Option<&str>) -> String {
+    let session_id = session_id.unwrap_or_default();
+    let nonce = generate_token();
+    let signature = new_mac(secret_key, session_id, &nonce)
+        .finalize()
+        .into_bytes();
+
+    format!("{}.{}.{}", session_id, nonce, HEXLOWER.encode(&signature))
+}
+
+/// Verify a token and return the session it was issued for, empty for
+/// visitors who weren't signed in
+pub fn verify(token: &str, secret_key: &str) -> Option<String> {
+    let mut parts = token.splitn(3, '.');
+    let session_id = parts.next()?;
+    let nonce = parts.next()?;
+    let signature = HEXLOWER.decode(parts.next()?.as_bytes()).ok()?;
+
+    new_mac(secret_key, session_id, nonce)
+        .verify_slice(&signature)
+        .ok()
+        .map(|_| session_id.to_string())
+}
+
+/// Compare the token of the cookie and the header without leaking where
+/// they differ
+pub fn matches(cookie: &str, header: &str) -> bool {
+    cookie.len() == header.len()
+        && cookie
+            .bytes()
+            .zip(header.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn new_mac(secret_key: &str, session_id: &str, nonce: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(b"csrf:");
+    mac.update(session_id.as_bytes());
+    mac.update(b":");
+    mac.update(nonce.as_bytes());
+    mac
+}
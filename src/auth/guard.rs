@@ -1,17 +1,186 @@
+use std::str::FromStr;
+
 use axum::{
     async_trait,
     extract::{FromRef, FromRequestParts},
-    http::{request::Parts, StatusCode},
+    http::{
+        header::{AUTHORIZATION, WWW_AUTHENTICATE},
+        request::Parts,
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
 };
 use axum_extra::extract::cookie::CookieJar;
+use bson::oid::ObjectId;
+use chrono::Utc;
+use serde_json::json;
+
+use crate::{
+    constants::auth::{AUTH_REALM, SESSION_TOUCH_INTERVAL_IN_SECONDS, TOKEN_COOKIE_KEY},
+    env::state::AppState,
+    models::{
+        api_token::{ApiToken, TokenScope, API_TOKEN_PREFIX},
+        revoked_token::RevokedToken,
+        session::Session,
+        user::User,
+    },
+    utils::client::ClientInfo,
+};
+
+use super::{permission::Permission, session::client_ip_hash, token::Token};
+
+/// Where the guards look for the access token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenSource {
+    /// `auth-token` cookie
+    Cookie,
+    /// `Authorization: Bearer <token>` header
+    Bearer,
+}
 
-use crate::{constants::auth::TOKEN_COOKIE_KEY, env::state::AppState, models::user::User};
+impl FromStr for TokenSource {
+    type Err = String;
 
-use super::token::Token;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "cookie" => Ok(TokenSource::Cookie),
+            "bearer" => Ok(TokenSource::Bearer),
+            _ => Err(format!("Unknown token source: {}", s)),
+        }
+    }
+}
+
+/// Rejection of the auth guards, with a `WWW-Authenticate` challenge
+#[derive(Debug)]
+pub struct AuthRejection {
+    status: StatusCode,
+    message: &'static str,
+    /// Error code of RFC 6750, `None` when no token was sent
+    error: Option<&'static str>,
+    /// Scope the request was missing
+    scope: Option<TokenScope>,
+}
+
+impl AuthRejection {
+    fn missing_token() -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Missing auth token",
+            error: None,
+            scope: None,
+        }
+    }
+
+    fn invalid_token(message: &'static str) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            message,
+            error: Some("invalid_token"),
+            scope: None,
+        }
+    }
+
+    fn insufficient_scope(scope: TokenScope) -> Self {
+        Self {
+            status: StatusCode::FORBIDDEN,
+            message: "API token is missing the required scope",
+            error: Some("insufficient_scope"),
+            scope: Some(scope),
+        }
+    }
+}
+
+impl IntoResponse for AuthRejection {
+    fn into_response(self) -> Response {
+        let mut challenge = format!("Bearer realm=\"{}\"", AUTH_REALM);
+
+        if let Some(error) = self.error {
+            challenge.push_str(&format!(
+                ", error=\"{}\", error_description=\"{}\"",
+                error, self.message
+            ));
+        }
+
+        if let Some(scope) = self.scope {
+            challenge.push_str(&format!(", scope=\"{}\"", scope.as_str()));
+        }
+
+        (self.status, [(WWW_AUTHENTICATE, challenge)], self.message).into_response()
+    }
+}
+
+/// Rejection of `AuthUser::require_permission`
+#[derive(Debug)]
+pub enum PermissionRejection {
+    /// The API token used is missing the scope of the permission
+    Scope(AuthRejection),
+    /// The permission can't be used with an API token
+    ApiToken,
+    /// The role of the user doesn't have the permission
+    Role(Permission),
+}
+
+impl IntoResponse for PermissionRejection {
+    fn into_response(self) -> Response {
+        match self {
+            PermissionRejection::Scope(rejection) => rejection.into_response(),
+            PermissionRejection::ApiToken => (
+                StatusCode::FORBIDDEN,
+                Json(json!({ "message": "API tokens can't be used for this" })),
+            )
+                .into_response(),
+            PermissionRejection::Role(permission) => (
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "message": "You don't have permission to do this",
+                    "permission": permission.as_str(),
+                })),
+            )
+                .into_response(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user: User,
+    /// Scopes of the API token used, `None` for sessions which can do anything
+    pub scopes: Option<Vec<TokenScope>>,
+    /// Session the access token was issued for, `None` for API tokens and
+    /// tokens issued before sessions were recorded
+    pub session_id: Option<ObjectId>,
+}
+
+impl AuthUser {
+    /// Make sure the credentials used allow `scope`
+    pub fn require_scope(&self, scope: TokenScope) -> Result<(), AuthRejection> {
+        match &self.scopes {
+            Some(scopes) if !scopes.contains(&scope) => {
+                Err(AuthRejection::insufficient_scope(scope))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Make sure the role of the user and the credentials used allow
+    /// `permission`
+    pub fn require_permission(&self, permission: Permission) -> Result<(), PermissionRejection> {
+        if self.scopes.is_some() {
+            match permission.scope() {
+                Some(scope) => self
+                    .require_scope(scope)
+                    .map_err(PermissionRejection::Scope)?,
+                None => return Err(PermissionRejection::ApiToken),
+            }
+        }
+
+        if !permission.is_granted_to(&self.user.role) {
+            return Err(PermissionRejection::Role(permission));
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -20,27 +189,21 @@ where
     AppState: FromRef<S>,
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = AuthRejection;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let state = AppState::from_ref(state);
-        let cookie_jar = CookieJar::from_headers(&parts.headers);
-        let token = cookie_jar.get(TOKEN_COOKIE_KEY);
+        let token = extract_token(&parts.headers, &state.auth_token_precedence);
 
         if token.is_none() {
-            return Err((StatusCode::UNAUTHORIZED, "Missing auth-token cookie"));
+            return Err(AuthRejection::missing_token());
         }
 
-        let token = token.unwrap().value();
-        let user = get_user_from_token(&token, &state).await;
+        let token = token.unwrap();
+        let client =
+            ClientInfo::from_http(&parts.headers, &parts.extensions, state.trusted_proxies);
 
-        if user.is_err() {
-            return Err((StatusCode::UNAUTHORIZED, "Invalid auth-token cookie"));
-        }
-
-        let user = user.unwrap();
-
-        Ok(AuthUser { user })
+        get_user_from_token(token, &state, &client).await
     }
 }
 
@@ -48,6 +211,51 @@ where
 pub struct AuthUserOrPublic {
     #[allow(dead_code)]
     pub user: Option<User>,
+    /// Scopes of the API token used, `None` for sessions and public requests
+    pub scopes: Option<Vec<TokenScope>>,
+}
+
+impl AuthUserOrPublic {
+    /// Make sure the credentials used allow `scope`, public requests are
+    /// left to the handler
+    pub fn require_scope(&self, scope: TokenScope) -> Result<(), AuthRejection> {
+        match &self.scopes {
+            Some(scopes) if !scopes.contains(&scope) => {
+                Err(AuthRejection::insufficient_scope(scope))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the request is from the user with `author_id`, rejecting API
+    /// tokens that aren't allowed to write as them
+    pub fn is_author(&self, author_id: Option<&ObjectId>) -> Result<bool, AuthRejection> {
+        let is_author = self
+            .user
+            .as_ref()
+            .is_some_and(|user| user.id.is_some() && user.id.as_ref() == author_id);
+
+        if is_author {
+            self.require_scope(TokenScope::CommentsWrite)?;
+        }
+
+        Ok(is_author)
+    }
+
+    /// Whether the request is from a user allowed `permission`, with
+    /// credentials allowing it
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        let Some(user) = &self.user else {
+            return false;
+        };
+        let is_scope_allowed = match (&self.scopes, permission.scope()) {
+            (None, _) => true,
+            (Some(scopes), Some(scope)) => scopes.contains(&scope),
+            (Some(_), None) => false,
+        };
+
+        is_scope_allowed && permission.is_granted_to(&user.role)
+    }
 }
 
 #[async_trait]
@@ -56,42 +264,157 @@ where
     AppState: FromRef<S>,
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, &'static str);
+    type Rejection = AuthRejection;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
         let state = AppState::from_ref(state);
-        let cookie_jar = CookieJar::from_headers(&parts.headers);
-        let token = cookie_jar.get(TOKEN_COOKIE_KEY);
+        let token = extract_token(&parts.headers, &state.auth_token_precedence);
 
         if token.is_none() {
-            return Ok(AuthUserOrPublic { user: None });
+            return Ok(AuthUserOrPublic {
+                user: None,
+                scopes: None,
+            });
         }
 
-        let token = token.unwrap().value();
-        let user = get_user_from_token(&token, &state).await;
+        let token = token.unwrap();
+        let client =
+            ClientInfo::from_http(&parts.headers, &parts.extensions, state.trusted_proxies);
+        let auth = get_user_from_token(token, &state, &client).await;
 
-        if user.is_err() {
-            return Ok(AuthUserOrPublic { user: None });
+        if auth.is_err() {
+            return Ok(AuthUserOrPublic {
+                user: None,
+                scopes: None,
+            });
         }
 
-        let user = user.unwrap();
+        let AuthUser { user, scopes, .. } = auth.unwrap();
 
-        Ok(AuthUserOrPublic { user: Some(user) })
+        Ok(AuthUserOrPublic {
+            user: Some(user),
+            scopes,
+        })
     }
 }
 
+/// Get the access token from the first source in `precedence` that has one
+pub fn extract_token(
+    headers: &HeaderMap,
+    precedence: &[TokenSource],
+) -> Option<(TokenSource, String)> {
+    precedence.iter().find_map(|source| {
+        let token = match source {
+            TokenSource::Cookie => CookieJar::from_headers(headers)
+                .get(TOKEN_COOKIE_KEY)
+                .map(|cookie| cookie.value().to_string()),
+            TokenSource::Bearer => headers
+                .get(AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split_once(' '))
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+                .map(|(_, token)| token.trim().to_string())
+                .filter(|token| !token.is_empty()),
+        };
+
+        token.map(|token| (*source, token))
+    })
+}
+
 async fn get_user_from_token(
-    token: &str,
+    (source, token): (TokenSource, String),
     state: &AppState,
-) -> Result<User, (StatusCode, &'static str)> {
-    let token_claims = Token::parse(&token, &state.jwt_secret)
-        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid auth-token cookie"))?;
+    client: &ClientInfo,
+) -> Result<AuthUser, AuthRejection> {
+    // API tokens are only accepted in the `Authorization` header
+    if source == TokenSource::Bearer && token.starts_with(API_TOKEN_PREFIX) {
+        return get_user_from_api_token(&token, state).await;
+    }
+
+    let token_claims = Token::parse(&token, &state.token_config)
+        .map_err(|_| AuthRejection::invalid_token("Invalid auth token"))?;
+
+    if !token_claims.jti.is_empty() {
+        let is_revoked = RevokedToken::is_revoked(&state.db, &token_claims.jti)
+            .await
+            .map_err(|e| {
+                log::error!("[Auth] Failed to check token revocation: {}", e);
+                AuthRejection::invalid_token("Invalid auth token")
+            })?;
+
+        if is_revoked {
+            return Err(AuthRejection::invalid_token("Revoked auth token"));
+        }
+    }
 
     let user = User::find_by_id(&state.db, &token_claims.sub).await;
 
     if user.is_err() {
-        return Err((StatusCode::UNAUTHORIZED, "Invalid auth-token cookie"));
+        return Err(AuthRejection::invalid_token("Invalid auth token"));
+    }
+
+    let user = user.unwrap();
+
+    if !user.accepts_token_issued_at(token_claims.iat) {
+        return Err(AuthRejection::invalid_token("Revoked auth token"));
+    }
+
+    if user.is_disabled() {
+        return Err(AuthRejection::invalid_token("Account is disabled"));
+    }
+
+    let session_id = token_claims.session_id();
+
+    if let Some(session_id) = session_id {
+        check_session(session_id, state, client).await?;
+    }
+
+    Ok(AuthUser {
+        user,
+        scopes: None,
+        session_id,
+    })
+}
+
+/// Make sure the session of the token wasn't revoked, and record its activity
+async fn check_session(
+    session_id: ObjectId,
+    state: &AppState,
+    client: &ClientInfo,
+) -> Result<(), AuthRejection> {
+    let session = Session::find_active(&state.db, session_id)
+        .await
+        .map_err(|e| {
+            log::error!("[Auth] Failed to get session: {}", e);
+            AuthRejection::invalid_token("Invalid auth token")
+        })?
+        .ok_or(AuthRejection::invalid_token("Revoked auth token"))?;
+
+    // Writing on every request is wasteful, a rough last activity is enough
+    if (Utc::now() - session.last_seen_at).num_seconds() >= SESSION_TOUCH_INTERVAL_IN_SECONDS {
+        if let Err(e) = Session::touch(&state.db, session_id, client_ip_hash(state, client)).await {
+            log::error!("[Auth] Failed to record session activity: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn get_user_from_api_token(token: &str, state: &AppState) -> Result<AuthUser, AuthRejection> {
+    let api_token = ApiToken::authenticate(&state.db, token)
+        .await
+        .map_err(|_| AuthRejection::invalid_token("Invalid API token"))?;
+    let user = User::find_by_id(&state.db, &api_token.user_id.to_string())
+        .await
+        .map_err(|_| AuthRejection::invalid_token("Invalid API token"))?;
+
+    if user.is_disabled() {
+        return Err(AuthRejection::invalid_token("Account is disabled"));
     }
 
-    Ok(user.unwrap())
+    Ok(AuthUser {
+        user,
+        scopes: Some(api_token.scopes),
+        session_id: None,
+    })
 }
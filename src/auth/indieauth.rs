@@ -0,0 +1,441 @@
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use hmac::{Hmac, Mac};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::LINK,
+    redirect, Url,
+};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::{constants::auth::VERIFIED_SITE_LIFETIME_IN_SECONDS, utils::ip::is_public_ip};
+
+/// Bytes of a profile page read while looking for its endpoints
+const MAX_PROFILE_PAGE_SIZE: usize = 512 * 1024;
+
+/// How long a request to a website or its authorization server can take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Redirects followed by a request to a website or its authorization server
+const MAX_REDIRECTS: usize = 5;
+
+/// Endpoints of the authorization server of a profile URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorizationServer {
+    pub authorization_endpoint: String,
+    /// Issuer identifier, unknown for servers only advertising their
+    /// authorization endpoint
+    pub issuer: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ServerMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct ProfileResponse {
+    me: Option<String>,
+    error: Option<String>,
+}
+
+/// Client for requests to the websites of users and their authorization
+/// servers
+///
+/// Those URLs come from users, so the client only connects to public
+/// addresses, including after redirects, and gives up on slow servers.
+pub fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .redirect(redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if let Err(e) = check_public_url(attempt.url()) {
+                attempt.error(e)
+            } else {
+                attempt.follow()
+            }
+        }))
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Resolver dropping the addresses that aren't public, so a domain name
+/// can't point the server at itself or its private network
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| is_public_ip(&address.ip().to_string()))
+                .collect::<Vec<_>>();
+
+            if addresses.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Make sure a URL uses http or https, and doesn't have a private IP address
+/// as host
+///
+/// Domain names are checked when resolved by the client.
+pub fn check_public_url(url: &Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{} doesn't use http or https", url));
+    }
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("{} has no host", url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+
+    if host.parse::<IpAddr>().is_ok() && !is_public_ip(host) {
+        return Err(format!("{} isn't a public address", host));
+    }
+
+    Ok(())
+}
+
+/// Canonicalize a profile URL as described in IndieAuth section 3.4
+///
+/// The scheme defaults to `https`, the path to `/`. URLs with a fragment,
+/// credentials, a port or an IP address as host are rejected.
+pub fn canonicalize_profile_url(input: &str) -> Result<Url, String> {
+    let input = input.trim();
+    let input = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("https://{}", input)
+    };
+    let url = Url::parse(&input).map_err(|e| e.to_string())?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("Profile URL must use http or https".to_string());
+    }
+
+    if url.fragment().is_some() {
+        return Err("Profile URL can't have a fragment".to_string());
+    }
+
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err("Profile URL can't have credentials".to_string());
+    }
+
+    if url.port().is_some() {
+        return Err("Profile URL can't have a port".to_string());
+    }
+
+    // `domain` is `None` for IP addresses
+    if !url
+        .domain()
+        .is_some_and(|domain| domain != "localhost" && domain.contains('.'))
+    {
+        return Err("Profile URL must have a domain name".to_string());
+    }
+
+    // `Url` lowercases the host and turns an empty path into `/`
+    Ok(url)
+}
+
+/// Find the authorization server of a profile URL
+///
+/// Looks for `indieauth-metadata`, then the legacy `authorization_endpoint`,
+/// in the `Link` headers and the `<link>` and `<a>` elements of the page.
+pub async fn discover(
+    client: &reqwest::Client,
+    profile_url: &Url,
+) -> Result<AuthorizationServer, String> {
+    let mut response = client
+        .get(profile_url.as_str())
+        .header(reqwest::header::ACCEPT, "text/html")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let base = response.url().clone();
+    let link_headers = response
+        .headers()
+        .get_all(LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .map(str::to_string)
+        .collect::<Vec<String>>();
+    let mut page = Vec::new();
+
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        page.extend_from_slice(&chunk);
+
+        if page.len() >= MAX_PROFILE_PAGE_SIZE {
+            break;
+        }
+    }
+
+    let page = String::from_utf8_lossy(&page);
+
+    if let Some(metadata_url) = find_rel(&base, &link_headers, &page, "indieauth-metadata") {
+        check_public_url(&metadata_url)?;
+
+        let metadata = client
+            .get(metadata_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json::<ServerMetadata>()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        check_public_url(
+            &Url::parse(&metadata.authorization_endpoint).map_err(|e| e.to_string())?,
+        )?;
+
+        return Ok(AuthorizationServer {
+            authorization_endpoint: metadata.authorization_endpoint,
+            issuer: Some(metadata.issuer),
+        });
+    }
+
+    let endpoint = find_rel(&base, &link_headers, &page, "authorization_endpoint")
+        .ok_or("No authorization endpoint found")?;
+
+    check_public_url(&endpoint)?;
+
+    Ok(AuthorizationServer {
+        authorization_endpoint: endpoint.to_string(),
+        issuer: None,
+    })
+}
+
+/// URL sending the user to their authorization server
+pub fn authorization_url(
+    server: &AuthorizationServer,
+    client_id: &str,
+    redirect_uri: &str,
+    state: &str,
+    code_challenge: &str,
+    me: &str,
+) -> Result<String, String> {
+    Url::parse_with_params(
+        &server.authorization_endpoint,
+        &[
+            ("response_type", "code"),
+            ("client_id", client_id),
+            ("redirect_uri", redirect_uri),
+            ("state", state),
+            ("code_challenge", code_challenge),
+            ("code_challenge_method", "S256"),
+            ("me", me),
+        ],
+    )
+    .map(|url| url.to_string())
+    .map_err(|e| e.to_string())
+}
+
+/// Redeem the authorization code for the profile URL of the user
+pub async fn redeem(
+    client: &reqwest::Client,
+    server: &AuthorizationServer,
+    client_id: &str,
+    redirect_uri: &str,
+    code: &str,
+    code_verifier: &str,
+) -> Result<Url, String> {
+    check_public_url(&Url::parse(&server.authorization_endpoint).map_err(|e| e.to_string())?)?;
+
+    let response = client
+        .post(&server.authorization_endpoint)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("client_id", client_id),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", code_verifier),
+        ])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json::<ProfileResponse>()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(error) = response.error {
+        return Err(format!("Authorization endpoint returned {}", error));
+    }
+
+    canonicalize_profile_url(
+        &response
+            .me
+            .ok_or("Authorization endpoint returned no profile")?,
+    )
+}
+
+/// Find the first URL with the relation `rel` in `Link` headers or in the
+/// `<link>` and `<a>` elements of an HTML page
+pub fn find_rel(base: &Url, link_headers: &[String], html: &str, rel: &str) -> Option<Url> {
+    let from_headers = link_headers
+        .iter()
+        .flat_map(|header| split_link_header(header))
+        .find(|(_, rels)| has_rel(rels, rel))
+        .map(|(href, _)| href);
+    let href = from_headers.or_else(|| {
+        html_tags(html)
+            .filter(|(name, _)| name == "link" || name == "a")
+            .find(|(_, attributes)| {
+                attribute(attributes, "rel").is_some_and(|rels| has_rel(&rels, rel))
+            })
+            .and_then(|(_, attributes)| attribute(&attributes, "href"))
+    })?;
+
+    base.join(&href).ok()
+}
+
+fn has_rel(rels: &str, rel: &str) -> bool {
+    rels.split_ascii_whitespace()
+        .any(|value| value.eq_ignore_ascii_case(rel))
+}
+
+/// Entries of a `Link` header as `(href, rel)` pairs
+fn split_link_header(header: &str) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    let mut rest = header;
+
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let href = rest[start + 1..start + end].to_string();
+        rest = &rest[start + end + 1..];
+        let params_end = rest.find('<').unwrap_or(rest.len());
+        let rels = rest[..params_end]
+            .split(';')
+            .filter_map(|param| param.trim().split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("rel"))
+            .map(|(_, value)| {
+                value
+                    .trim()
+                    .trim_end_matches(',')
+                    .trim_matches('"')
+                    .to_string()
+            });
+
+        if let Some(rels) = rels {
+            entries.push((href, rels));
+        }
+    }
+
+    entries
+}
+
+/// Start tags of an HTML page as lowercased names with their raw attributes
+fn html_tags(html: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    html.split('<').skip(1).filter_map(|tag| {
+        let tag = &tag[..tag.find('>')?];
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+
+        Some((
+            tag[..name_end].to_ascii_lowercase(),
+            tag[name_end..].to_string(),
+        ))
+    })
+}
+
+/// Value of an attribute in the raw attributes of a tag
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        let key_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = rest[key_end..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(value) => {
+                let value = value.trim_start();
+                let (value, remaining) = match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = value[1..].find(quote).map_or(value.len(), |end| end + 1);
+                        (&value[1..end], &value[(end + 1).min(value.len())..])
+                    }
+                    _ => {
+                        let end = value
+                            .find(|c: char| c.is_ascii_whitespace())
+                            .unwrap_or(value.len());
+                        (&value[..end], &value[end..])
+                    }
+                };
+
+                rest = remaining;
+                value
+            }
+            None => "",
+        };
+
+        if key.eq_ignore_ascii_case(name) {
+            return Some(value.replace("&amp;", "&"));
+        }
+    }
+}
+
+/// Issue a token proving the holder owns the website at `me`
+///
+/// The token has the form `<unix expiry>.<hex signature>.<base64url me>`.
+pub fn issue_proof(me: &str, secret_key: &str) -> String {
+    let expires_at = Utc::now().timestamp() + VERIFIED_SITE_LIFETIME_IN_SECONDS;
+    let signature = new_mac(secret_key, me, expires_at).finalize().into_bytes();
+
+    format!(
+        "{}.{}.{}",
+        expires_at,
+        HEXLOWER.encode(&signature),
+        BASE64URL_NOPAD.encode(me.as_bytes())
+    )
+}
+
+/// Verify a proof token and return the website it was issued for
+pub fn verify_proof(token: &str, secret_key: &str) -> Option<String> {
+    let mut parts = token.splitn(3, '.');
+    let expires_at = parts.next()?.parse::<i64>().ok()?;
+    let signature = HEXLOWER.decode(parts.next()?.as_bytes()).ok()?;
+    let me = String::from_utf8(BASE64URL_NOPAD.decode(parts.next()?.as_bytes()).ok()?).ok()?;
+
+    if expires_at < Utc::now().timestamp() {
+        return None;
+    }
+
+    new_mac(secret_key, &me, expires_at)
+        .verify_slice(&signature)
+        .ok()
+        .map(|_| me)
+}
+
+fn new_mac(secret_key: &str, me: &str, expires_at: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(b"verified-site:");
+    mac.update(me.as_bytes());
+    mac.update(b":");
+    mac.update(expires_at.to_string().as_bytes());
+    mac
+}
@@ -0,0 +1,316 @@
+use std::{fmt, str::FromStr};
+
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use hmac::{Hmac, Mac};
+use openssl::{
+    bn::{BigNum, BigNumContext},
+    ecdsa::EcdsaSig,
+    hash::MessageDigest,
+    nid::Nid,
+    pkey::{Id, PKey, Private, Public},
+    sign::{Signer, Verifier},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+/// Size of the coordinates of P-256 points and of the halves of ES256
+/// signatures
+const P256_FIELD_SIZE: i32 = 32;
+
+/// Signature algorithms of access tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Algorithm {
+    #[serde(rename = "HS256")]
+    Hs256,
+    #[serde(rename = "RS256")]
+    Rs256,
+    #[serde(rename = "ES256")]
+    Es256,
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+}
+
+impl Algorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Hs256 => "HS256",
+            Algorithm::Rs256 => "RS256",
+            Algorithm::Es256 => "ES256",
+            Algorithm::EdDsa => "EdDSA",
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "HS256" => Ok(Algorithm::Hs256),
+            "RS256" => Ok(Algorithm::Rs256),
+            "ES256" => Ok(Algorithm::Es256),
+            "EdDSA" => Ok(Algorithm::EdDsa),
+            _ => Err(format!("Unsupported algorithm: {}", s)),
+        }
+    }
+}
+
+enum Material {
+    Secret(Vec<u8>),
+    /// Key pair, or only the public key of keys that just verify
+    Asymmetric {
+        private: Option<PKey<Private>>,
+        public: PKey<Public>,
+    },
+}
+
+/// Key signing or verifying access tokens
+pub struct Key {
+    /// `kid` header of the tokens signed with the key
+    pub id: String,
+    pub algorithm: Algorithm,
+    material: Material,
+}
+
+impl Key {
+    /// HS256 key, identified by the start of the SHA-256 hash of the secret
+    pub fn from_secret(secret: &str) -> Self {
+        Self {
+            id: HEXLOWER.encode(&Sha256::digest(secret.as_bytes())[..8]),
+            algorithm: Algorithm::Hs256,
+            material: Material::Secret(secret.as_bytes().to_vec()),
+        }
+    }
+
+    /// RS256, ES256 or EdDSA key from a private or public PEM key,
+    /// identified by its JWK thumbprint
+    pub fn from_pem(pem: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let (private, public) = match PKey::private_key_from_pem(pem) {
+            Ok(private) => {
+                let public = PKey::public_key_from_der(&private.public_key_to_der()?)?;
+
+                (Some(private), public)
+            }
+            Err(_) => (None, PKey::public_key_from_pem(pem)?),
+        };
+        let algorithm = match public.id() {
+            Id::RSA => Algorithm::Rs256,
+            Id::EC if public.ec_key()?.group().curve_name() == Some(Nid::X9_62_PRIME256V1) => {
+                Algorithm::Es256
+            }
+            Id::ED25519 => Algorithm::EdDsa,
+            _ => return Err("Only RSA, P-256 and Ed25519 keys are supported".into()),
+        };
+        let mut key = Self {
+            id: String::new(),
+            algorithm,
+            material: Material::Asymmetric { private, public },
+        };
+        key.id = key.thumbprint()?;
+
+        Ok(key)
+    }
+
+    pub fn from_pem_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_pem(&std::fs::read(path)?)
+    }
+
+    pub fn can_sign(&self) -> bool {
+        match &self.material {
+            Material::Secret(_) => true,
+            Material::Asymmetric { private, .. } => private.is_some(),
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let private = match &self.material {
+            Material::Secret(secret) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)?;
+                mac.update(message);
+
+                return Ok(mac.finalize().into_bytes().to_vec());
+            }
+            Material::Asymmetric {
+                private: Some(private),
+                ..
+            } => private,
+            Material::Asymmetric { private: None, .. } => {
+                return Err("Key can only verify".into());
+            }
+        };
+
+        match self.algorithm {
+            Algorithm::EdDsa => {
+                Ok(Signer::new_without_digest(private)?.sign_oneshot_to_vec(message)?)
+            }
+            _ => {
+                let mut signer = Signer::new(MessageDigest::sha256(), private)?;
+                signer.update(message)?;
+                let signature = signer.sign_to_vec()?;
+
+                if self.algorithm != Algorithm::Es256 {
+                    return Ok(signature);
+                }
+
+                // JWS uses the raw `r || s` form instead of DER
+                let signature = EcdsaSig::from_der(&signature)?;
+                let mut raw = signature.r().to_vec_padded(P256_FIELD_SIZE)?;
+                raw.extend(signature.s().to_vec_padded(P256_FIELD_SIZE)?);
+
+                Ok(raw)
+            }
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        let public = match &self.material {
+            Material::Secret(secret) => {
+                let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret) else {
+                    return false;
+                };
+                mac.update(message);
+
+                return mac.verify_slice(signature).is_ok();
+            }
+            Material::Asymmetric { public, .. } => public,
+        };
+
+        self.verify_asymmetric(public, message, signature)
+            .unwrap_or(false)
+    }
+
+    fn verify_asymmetric(
+        &self,
+        public: &PKey<Public>,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, openssl::error::ErrorStack> {
+        match self.algorithm {
+            Algorithm::EdDsa => {
+                Verifier::new_without_digest(public)?.verify_oneshot(signature, message)
+            }
+            Algorithm::Es256 => {
+                if signature.len() != 2 * P256_FIELD_SIZE as usize {
+                    return Ok(false);
+                }
+
+                let (r, s) = signature.split_at(P256_FIELD_SIZE as usize);
+                let signature = EcdsaSig::from_private_components(
+                    BigNum::from_slice(r)?,
+                    BigNum::from_slice(s)?,
+                )?;
+                let mut verifier = Verifier::new(MessageDigest::sha256(), public)?;
+                verifier.update(message)?;
+
+                verifier.verify(&signature.to_der()?)
+            }
+            _ => {
+                let mut verifier = Verifier::new(MessageDigest::sha256(), public)?;
+                verifier.update(message)?;
+
+                verifier.verify(signature)
+            }
+        }
+    }
+
+    /// Public JWK of the key, `None` for secrets
+    pub fn to_jwk(&self) -> Option<Value> {
+        let mut jwk = self.public_members().ok()??;
+        jwk["kid"] = json!(self.id);
+        jwk["alg"] = json!(self.algorithm.as_str());
+        jwk["use"] = json!("sig");
+
+        Some(jwk)
+    }
+
+    /// Required members of the public JWK
+    fn public_members(&self) -> Result<Option<Value>, openssl::error::ErrorStack> {
+        let Material::Asymmetric { public, .. } = &self.material else {
+            return Ok(None);
+        };
+
+        let members = match self.algorithm {
+            Algorithm::Rs256 => {
+                let rsa = public.rsa()?;
+
+                json!({
+                    "e": BASE64URL_NOPAD.encode(&rsa.e().to_vec()),
+                    "kty": "RSA",
+                    "n": BASE64URL_NOPAD.encode(&rsa.n().to_vec()),
+                })
+            }
+            Algorithm::Es256 => {
+                let ec = public.ec_key()?;
+                let mut x = BigNum::new()?;
+                let mut y = BigNum::new()?;
+                let mut context = BigNumContext::new()?;
+                ec.public_key()
+                    .affine_coordinates(ec.group(), &mut x, &mut y, &mut context)?;
+
+                json!({
+                    "crv": "P-256",
+                    "kty": "EC",
+                    "x": BASE64URL_NOPAD.encode(&x.to_vec_padded(P256_FIELD_SIZE)?),
+                    "y": BASE64URL_NOPAD.encode(&y.to_vec_padded(P256_FIELD_SIZE)?),
+                })
+            }
+            Algorithm::EdDsa => json!({
+                "crv": "Ed25519",
+                "kty": "OKP",
+                "x": BASE64URL_NOPAD.encode(&public.raw_public_key()?),
+            }),
+            Algorithm::Hs256 => return Ok(None),
+        };
+
+        Ok(Some(members))
+    }
+
+    /// RFC 7638 thumbprint, the members being listed in the lexicographic
+    /// order it requires
+    fn thumbprint(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let members = self.public_members()?.ok_or("Secrets have no thumbprint")?;
+
+        Ok(BASE64URL_NOPAD.encode(&Sha256::digest(serde_json::to_vec(&members)?)))
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("id", &self.id)
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+/// Keys accepted for access tokens
+///
+/// Only the first key signs, the others keep verifying tokens issued before
+/// a rotation until they expire.
+#[derive(Debug, Clone)]
+pub struct Keyring {
+    keys: std::sync::Arc<Vec<Key>>,
+}
+
+impl Keyring {
+    pub fn new(signing_key: Key, previous: Vec<Key>) -> Result<Self, String> {
+        if !signing_key.can_sign() {
+            return Err(format!("Key {} can only verify", signing_key.id));
+        }
+
+        let keys = std::iter::once(signing_key).chain(previous).collect();
+
+        Ok(Self {
+            keys: std::sync::Arc::new(keys),
+        })
+    }
+
+    pub fn signing_key(&self) -> &Key {
+        &self.keys[0]
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+}
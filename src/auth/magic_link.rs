@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{constants::auth::MAGIC_LINK_LIFETIME_IN_SECONDS, utils::encryption::generate_token};
+
+/// Verified magic link token
+#[derive(Debug, Clone, PartialEq)]
+pub struct MagicLink {
+    /// Lowercased address the link was mailed to
+    pub email: String,
+    /// Random ID of the token, recorded once it's used
+    pub jti: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Issue a token signing in as the owner of `email`
+///
+/// The token has the form `<unix expiry>.<jti>.<hex signature>.<base64url email>`.
+pub fn issue(email: &str, secret_key: &str) -> String {
+    let email = email.to_lowercase();
+    let expires_at = Utc::now().timestamp() + MAGIC_LINK_LIFETIME_IN_SECONDS;
+    let jti = generate_token();
+    let signature = new_mac(secret_key, &email, &jti, expires_at)
+        .finalize()
+        .into_bytes();
+
+    format!(
+        "{}.{}.{}.{}",
+        expires_at,
+        jti,
+        HEXLOWER.encode(&signature),
+        BASE64URL_NOPAD.encode(email.as_bytes())
+    )
+}
+
+/// Verify a magic link token
+///
+/// Returns `None` if the token is malformed, expired or the signature doesn't
+/// match. Whether it was already used is up to the caller to check.
+pub fn verify(token: &str, secret_key: &str) -> Option<MagicLink> {
+    let mut parts = token.splitn(4, '.');
+    let expires_at = parts.next()?.parse::<i64>().ok()?;
+    let jti = parts.next()?;
+    let signature = HEXLOWER.decode(parts.next()?.as_bytes()).ok()?;
+    let email = String::from_utf8(BASE64URL_NOPAD.decode(parts.next()?.as_bytes()).ok()?).ok()?;
+
+    if expires_at < Utc::now().timestamp() {
+        return None;
+    }
+
+    new_mac(secret_key, &email, jti, expires_at)
+        .verify_slice(&signature)
+        .ok()?;
+
+    Some(MagicLink {
+        email,
+        jti: jti.to_string(),
+        expires_at: DateTime::from_timestamp(expires_at, 0)?,
+    })
+}
+
+fn new_mac(secret_key: &str, email: &str, jti: &str, expires_at: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(b"magic-link:");
+    mac.update(email.as_bytes());
+    mac.update(b":");
+    mac.update(jti.as_bytes());
+    mac.update(b":");
+    mac.update(expires_at.to_string().as_bytes());
+    mac
+}
@@ -1,2 +1,17 @@
+mod __tests__;
+
+pub mod challenge;
+pub mod cookie;
+pub mod csrf;
 pub mod guard;
+pub mod indieauth;
+pub mod keyring;
+pub mod magic_link;
+pub mod oauth;
+pub mod passkey;
+pub mod password_reset;
+pub mod permission;
+pub mod second_factor;
+pub mod session;
+pub mod signup;
 pub mod token;
@@ -0,0 +1,363 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Duration, Utc};
+use data_encoding::BASE64URL_NOPAD;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::constants::auth::OAUTH_DISCOVERY_CACHE_LIFETIME_IN_SECONDS;
+
+const GITHUB_AUTHORIZATION_ENDPOINT: &str = "https://github.com/login/oauth/authorize";
+const GITHUB_TOKEN_ENDPOINT: &str = "https://github.com/login/oauth/access_token";
+const GITHUB_USER_ENDPOINT: &str = "https://api.github.com/user";
+
+/// How long a request to the provider can take, so a slow provider can't
+/// hold the sign in forever
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProviderKind {
+    /// GitHub OAuth app, which doesn't support OIDC
+    Github,
+    /// Any OpenID Connect provider
+    Oidc,
+}
+
+/// OAuth provider configured with `OAUTH_<NAME>_*` variables
+///
+/// The provider named `github` is a GitHub OAuth app, the others are OIDC
+/// providers found through `OAUTH_<NAME>_DISCOVERY_URL`.
+#[derive(Debug, Clone)]
+pub struct OAuthProvider {
+    pub name: String,
+    pub kind: ProviderKind,
+    pub client_id: String,
+    pub client_secret: String,
+    /// URL of the OpenID configuration document, only for OIDC providers
+    pub discovery_url: Option<String>,
+    /// Endpoints of the last configuration document fetched
+    discovered: Arc<RwLock<Option<Discovered>>>,
+    /// Client of every request to the provider
+    client: reqwest::Client,
+}
+
+/// Account at the provider the user signed in with
+#[derive(Debug, Clone)]
+pub struct ExternalAccount {
+    /// Stable ID of the account
+    pub subject: String,
+    /// Name to give to a new user
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+struct Endpoints {
+    authorization: String,
+    token: String,
+    issuer: Option<String>,
+}
+
+#[derive(Debug)]
+struct Discovered {
+    endpoints: Endpoints,
+    fetched_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct DiscoveryDocument {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    id_token: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    /// A single audience or a list of them
+    aud: Value,
+    exp: i64,
+    nonce: Option<String>,
+    preferred_username: Option<String>,
+    name: Option<String>,
+    email: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GithubUser {
+    id: i64,
+    login: String,
+}
+
+impl OAuthProvider {
+    pub fn new(
+        name: &str,
+        kind: ProviderKind,
+        client_id: String,
+        client_secret: String,
+        discovery_url: Option<String>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            client_id,
+            client_secret,
+            discovery_url,
+            discovered: Arc::default(),
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to build the OAuth HTTP client"),
+        }
+    }
+
+    pub fn from_env(name: &str) -> Self {
+        let prefix = format!("OAUTH_{}", name.to_uppercase().replace('-', "_"));
+        let var = |key: &str| {
+            std::env::var(format!("{}_{}", prefix, key))
+                .ok()
+                .filter(|value| !value.is_empty())
+        };
+        let kind = if name == "github" {
+            ProviderKind::Github
+        } else {
+            ProviderKind::Oidc
+        };
+        let discovery_url = var("DISCOVERY_URL");
+
+        if kind == ProviderKind::Oidc && discovery_url.is_none() {
+            panic!("{}_DISCOVERY_URL is not set", prefix);
+        }
+
+        Self::new(
+            name,
+            kind,
+            var("CLIENT_ID").unwrap_or_else(|| panic!("{}_CLIENT_ID is not set", prefix)),
+            var("CLIENT_SECRET").unwrap_or_else(|| panic!("{}_CLIENT_SECRET is not set", prefix)),
+            discovery_url,
+        )
+    }
+
+    /// URL sending the user to the provider to authorize the sign in
+    pub async fn authorization_url(
+        &self,
+        redirect_uri: &str,
+        state: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<String, String> {
+        let endpoints = self.endpoints().await?;
+        let code_challenge = code_challenge(code_verifier);
+        let mut params = vec![
+            ("response_type", "code"),
+            ("client_id", &self.client_id),
+            ("redirect_uri", redirect_uri),
+            ("state", state),
+            ("code_challenge", &code_challenge),
+            ("code_challenge_method", "S256"),
+        ];
+
+        match self.kind {
+            ProviderKind::Github => params.push(("scope", "read:user")),
+            ProviderKind::Oidc => {
+                params.push(("scope", "openid profile email"));
+                params.push(("nonce", nonce));
+            }
+        }
+
+        Url::parse_with_params(&endpoints.authorization, &params)
+            .map(|url| url.to_string())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Exchange the authorization code for the account of the user
+    pub async fn exchange(
+        &self,
+        redirect_uri: &str,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<ExternalAccount, String> {
+        let endpoints = self.endpoints().await?;
+        let token_response = self
+            .client
+            .post(&endpoints.token)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json::<TokenResponse>()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if let Some(error) = token_response.error {
+            return Err(format!("Token endpoint returned {}", error));
+        }
+
+        match self.kind {
+            ProviderKind::Github => {
+                let access_token = token_response
+                    .access_token
+                    .ok_or("Token endpoint returned no access token")?;
+
+                github_account(&self.client, &access_token).await
+            }
+            ProviderKind::Oidc => {
+                let id_token = token_response
+                    .id_token
+                    .ok_or("Token endpoint returned no ID token")?;
+
+                self.verify_id_token(&id_token, endpoints.issuer.as_deref(), nonce)
+            }
+        }
+    }
+
+    /// Endpoints of the provider, fetched from the configuration document at
+    /// most once per `OAUTH_DISCOVERY_CACHE_LIFETIME_IN_SECONDS`
+    async fn endpoints(&self) -> Result<Endpoints, String> {
+        let discovery_url = match (&self.kind, &self.discovery_url) {
+            (ProviderKind::Oidc, Some(discovery_url)) => discovery_url,
+            _ => {
+                return Ok(Endpoints {
+                    authorization: GITHUB_AUTHORIZATION_ENDPOINT.to_string(),
+                    token: GITHUB_TOKEN_ENDPOINT.to_string(),
+                    issuer: None,
+                });
+            }
+        };
+
+        let fresh_after = Utc::now() - Duration::seconds(OAUTH_DISCOVERY_CACHE_LIFETIME_IN_SECONDS);
+
+        if let Some(discovered) = self
+            .discovered
+            .read()
+            .unwrap()
+            .as_ref()
+            .filter(|discovered| discovered.fetched_at > fresh_after)
+        {
+            return Ok(discovered.endpoints.clone());
+        }
+
+        let document = self
+            .client
+            .get(discovery_url)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json::<DiscoveryDocument>()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let endpoints = Endpoints {
+            authorization: document.authorization_endpoint,
+            token: document.token_endpoint,
+            issuer: Some(document.issuer),
+        };
+
+        *self.discovered.write().unwrap() = Some(Discovered {
+            endpoints: endpoints.clone(),
+            fetched_at: Utc::now(),
+        });
+
+        Ok(endpoints)
+    }
+
+    /// Check the claims of an ID token
+    ///
+    /// The token comes straight from the token endpoint over TLS, so as OIDC
+    /// Core 3.1.3.7 allows, its signature isn't checked.
+    pub fn verify_id_token(
+        &self,
+        id_token: &str,
+        issuer: Option<&str>,
+        nonce: &str,
+    ) -> Result<ExternalAccount, String> {
+        let payload = id_token.split('.').nth(1).ok_or("Malformed ID token")?;
+        let payload = BASE64URL_NOPAD
+            .decode(payload.trim_end_matches('=').as_bytes())
+            .map_err(|e| e.to_string())?;
+        let claims =
+            serde_json::from_slice::<IdTokenClaims>(&payload).map_err(|e| e.to_string())?;
+
+        if issuer.is_some_and(|issuer| issuer != claims.iss) {
+            return Err(format!("Unexpected ID token issuer {}", claims.iss));
+        }
+
+        let audiences = match &claims.aud {
+            Value::String(audience) => vec![audience.as_str()],
+            Value::Array(audiences) => audiences.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+
+        if !audiences.contains(&self.client_id.as_str()) {
+            return Err("ID token isn't meant for this client".to_string());
+        }
+
+        if claims.exp < Utc::now().timestamp() {
+            return Err("Expired ID token".to_string());
+        }
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err("ID token nonce doesn't match".to_string());
+        }
+
+        let name = claims
+            .preferred_username
+            .or(claims.name)
+            .or_else(|| {
+                claims
+                    .email
+                    .and_then(|email| email.split('@').next().map(str::to_string))
+            })
+            .unwrap_or_default();
+
+        Ok(ExternalAccount {
+            subject: claims.sub,
+            name,
+        })
+    }
+}
+
+async fn github_account(
+    client: &reqwest::Client,
+    access_token: &str,
+) -> Result<ExternalAccount, String> {
+    let user = client
+        .get(GITHUB_USER_ENDPOINT)
+        .bearer_auth(access_token)
+        .header(reqwest::header::USER_AGENT, "marshallku-blog")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .json::<GithubUser>()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(ExternalAccount {
+        subject: user.id.to_string(),
+        name: user.login,
+    })
+}
+
+/// PKCE `S256` code challenge of a code verifier
+pub fn code_challenge(code_verifier: &str) -> String {
+    BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes()))
+}
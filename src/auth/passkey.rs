@@ -0,0 +1,37 @@
+use bson::oid::ObjectId;
+use data_encoding::BASE64URL_NOPAD;
+use webauthn_rs::prelude::{Url, Uuid, Webauthn, WebauthnBuilder, WebauthnError};
+
+use crate::constants::auth::AUTH_REALM;
+
+/// Build the WebAuthn relying party
+///
+/// The relying party ID is `cookie_domain` without its leading dot, so
+/// passkeys work on every subdomain the auth cookie is sent to. `origin` is
+/// where the ceremonies run, `https://<relying party ID>` by default.
+pub fn relying_party(cookie_domain: &str, origin: Option<&str>) -> Result<Webauthn, WebauthnError> {
+    let rp_id = cookie_domain.trim_start_matches('.');
+    let origin = match origin {
+        Some(origin) => Url::parse(origin),
+        None => Url::parse(&format!("https://{}", rp_id)),
+    }
+    .map_err(|_| WebauthnError::Configuration)?;
+
+    WebauthnBuilder::new(rp_id, &origin)?
+        .rp_name(AUTH_REALM)
+        .allow_subdomains(true)
+        .build()
+}
+
+/// WebAuthn user handle of a user, derived from their id
+pub fn user_handle(user_id: ObjectId) -> Uuid {
+    let mut bytes = [0; 16];
+    bytes[..12].copy_from_slice(&user_id.bytes());
+
+    Uuid::from_bytes(bytes)
+}
+
+/// Base64url encoded credential ID, as stored with the passkey
+pub fn encode_credential_id(credential_id: &[u8]) -> String {
+    BASE64URL_NOPAD.encode(credential_id)
+}
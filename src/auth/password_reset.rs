@@ -0,0 +1,13 @@
+/// Where the user can use the reset token
+///
+/// `PASSWORD_RESET_URL` is the page of the frontend taking the token as the
+/// `token` query parameter. Without it, the bare token is returned.
+pub fn reset_link(token: &str) -> String {
+    match std::env::var("PASSWORD_RESET_URL") {
+        Ok(url) if !url.is_empty() => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!("{}{}token={}", url, separator, token)
+        }
+        _ => token.to_string(),
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::{api_token::TokenScope, user::UserRole};
+
+/// Action restricted to some roles
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Permission {
+    /// Read data about comments that isn't public, such as fingerprints
+    #[serde(rename = "comment.read")]
+    CommentRead,
+    /// Edit comments written by others
+    #[serde(rename = "comment.edit")]
+    CommentEdit,
+    #[serde(rename = "comment.delete")]
+    CommentDelete,
+    /// Ban and unban commenters
+    #[serde(rename = "ban.manage")]
+    BanManage,
+    #[serde(rename = "invite.manage")]
+    InviteManage,
+    /// Create and revoke API tokens
+    #[serde(rename = "token.manage")]
+    TokenManage,
+    /// Manage accounts of other users
+    #[serde(rename = "user.manage")]
+    UserManage,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::CommentRead => "comment.read",
+            Permission::CommentEdit => "comment.edit",
+            Permission::CommentDelete => "comment.delete",
+            Permission::BanManage => "ban.manage",
+            Permission::InviteManage => "invite.manage",
+            Permission::TokenManage => "token.manage",
+            Permission::UserManage => "user.manage",
+        }
+    }
+
+    /// Scope an API token needs to use the permission, `None` for
+    /// permissions only sessions can use
+    pub fn scope(&self) -> Option<TokenScope> {
+        match self {
+            Permission::CommentRead => Some(TokenScope::CommentsRead),
+            Permission::CommentEdit | Permission::BanManage => Some(TokenScope::CommentsModerate),
+            Permission::CommentDelete => Some(TokenScope::CommentsDelete),
+            Permission::InviteManage | Permission::TokenManage | Permission::UserManage => None,
+        }
+    }
+
+    /// Whether users with the role have the permission
+    pub fn is_granted_to(&self, role: &UserRole) -> bool {
+        match role {
+            UserRole::Root => true,
+            UserRole::Moderator => matches!(
+                self,
+                Permission::CommentRead
+                    | Permission::CommentEdit
+                    | Permission::CommentDelete
+                    | Permission::BanManage
+            ),
+            UserRole::User => false,
+        }
+    }
+}
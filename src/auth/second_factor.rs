@@ -0,0 +1,35 @@
+use chrono::Utc;
+use mongodb::{error::Error, Database};
+
+use crate::{
+    models::user::User,
+    utils::{encryption::hash_token, totp},
+};
+
+/// Check a TOTP code, or a recovery code, of a user with TOTP enabled
+///
+/// The code is spent when it matches, so it can't be used again.
+pub async fn verify(db: &Database, user: &User, code: &str) -> Result<bool, Error> {
+    let (user_id, user_totp) = match (user.id, &user.totp) {
+        (Some(user_id), Some(user_totp)) if user_totp.enabled_at.is_some() => (user_id, user_totp),
+        _ => return Ok(false),
+    };
+
+    let code = code.trim();
+
+    if code.chars().all(|c| c.is_ascii_digit()) {
+        return match totp::verify(
+            &user_totp.secret,
+            code,
+            Utc::now().timestamp(),
+            user_totp.last_used_step,
+        ) {
+            Some(step) => User::use_totp_step(db, user_id, step).await,
+            None => Ok(false),
+        };
+    }
+
+    let code_hash = hash_token(&totp::normalize_recovery_code(code));
+
+    User::use_recovery_code(db, user_id, &code_hash).await
+}
@@ -0,0 +1,118 @@
+use axum::http::{header::SET_COOKIE, HeaderMap};
+use axum_extra::extract::cookie::CookieJar;
+use mongodb::bson::oid::ObjectId;
+
+use crate::{
+    constants::auth::{REFRESH_TOKEN_COOKIE_KEY, TOKEN_COOKIE_KEY},
+    env::state::AppState,
+    models::{refresh_token::RefreshToken, session::Session, user::User},
+    utils::{client::ClientInfo, encryption::hash_ip},
+};
+
+use super::{
+    cookie::{access_token_cookie, csrf_cookie, refresh_token_cookie},
+    csrf,
+    token::Token,
+};
+
+/// Sign the user in, recording a session of the client which is also the
+/// family of its refresh tokens
+///
+/// Returns the headers setting the access and refresh token cookies.
+pub async fn issue(
+    state: &AppState,
+    user: &User,
+    client: &ClientInfo,
+) -> Result<HeaderMap, Box<dyn std::error::Error>> {
+    let user_id = user.id.ok_or("User has no id")?;
+    let session = Session::create(
+        &state.db,
+        user_id,
+        client.user_agent.clone(),
+        client_ip_hash(state, client),
+    )
+    .await?;
+    let session_id = session.id.ok_or("Session has no id")?;
+    let access_token = Token::from_user(user, session_id, &state.token_config)?;
+    let refresh_token = RefreshToken::issue(&state.db, user_id, session_id).await?;
+
+    Ok(cookie_headers(
+        state,
+        access_token,
+        refresh_token,
+        session_id,
+    ))
+}
+
+/// Headers setting the access and refresh token cookies, along with a fresh
+/// CSRF token
+pub fn cookie_headers(
+    state: &AppState,
+    access_token: String,
+    refresh_token: String,
+    session_id: ObjectId,
+) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+
+    headers.append(
+        SET_COOKIE,
+        access_token_cookie(access_token, state.cookie_domain.clone())
+            .to_string()
+            .parse()
+            .unwrap(),
+    );
+    headers.append(
+        SET_COOKIE,
+        refresh_token_cookie(refresh_token, state.cookie_domain.clone())
+            .to_string()
+            .parse()
+            .unwrap(),
+    );
+    headers.append(
+        SET_COOKIE,
+        csrf_cookie(
+            csrf::issue(&state.jwt_secret, Some(&session_id.to_hex())),
+            state.cookie_domain.clone(),
+        )
+        .to_string()
+        .parse()
+        .unwrap(),
+    );
+
+    headers
+}
+
+/// Session the cookies of the request belong to, `None` when they don't
+/// belong to any
+///
+/// Taken from the access token, or else from the refresh token which is only
+/// sent to the auth endpoints.
+pub async fn from_cookies(
+    state: &AppState,
+    cookie_jar: &CookieJar,
+) -> Result<Option<String>, mongodb::error::Error> {
+    let session_id = cookie_jar
+        .get(TOKEN_COOKIE_KEY)
+        .and_then(|cookie| Token::parse(cookie.value(), &state.token_config).ok())
+        .map(|claims| claims.sid)
+        .filter(|session_id| !session_id.is_empty());
+
+    if session_id.is_some() {
+        return Ok(session_id);
+    }
+
+    match cookie_jar.get(REFRESH_TOKEN_COOKIE_KEY) {
+        Some(cookie) => Ok(RefreshToken::find_family(&state.db, cookie.value())
+            .await?
+            .map(|family_id| family_id.to_hex())),
+        None => Ok(None),
+    }
+}
+
+/// Hash of the IP address of the client, as stored in sessions
+pub fn client_ip_hash(state: &AppState, client: &ClientInfo) -> Option<String> {
+    client
+        .ip
+        .as_ref()
+        .map(|ip| hash_ip(ip, &state.ip_hash_salt))
+}
@@ -0,0 +1,28 @@
+use std::str::FromStr;
+
+/// Who can create an account through `POST /auth/signup`
+///
+/// The first account is always allowed, so a fresh install can create its
+/// Root user whatever the mode is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignupMode {
+    /// Anyone can sign up
+    Open,
+    /// Only people with an invite code can sign up
+    Invite,
+    /// Nobody can sign up
+    Closed,
+}
+
+impl FromStr for SignupMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "open" => Ok(SignupMode::Open),
+            "invite" => Ok(SignupMode::Invite),
+            "closed" => Ok(SignupMode::Closed),
+            _ => Err(format!("Unknown signup mode: {}", s)),
+        }
+    }
+}
@@ -1,55 +1,260 @@
-use std::collections::BTreeMap;
+use std::fmt;
 
+use bson::oid::ObjectId;
 use chrono::Utc;
-use hmac::{Hmac, Mac};
-use jwt::{Header, SignWithKey, VerifyWithKey};
-use serde::{Deserialize, Serialize};
-use sha2::Sha256;
+use data_encoding::BASE64URL_NOPAD;
+use serde::{de, Deserialize, Deserializer, Serialize};
 
-use crate::{constants::time::ONE_DAY_IN_SECONDS, models::user::User};
+use crate::{constants::auth::ACCESS_TOKEN_LIFETIME_IN_SECONDS, models::user::User};
 
+use super::keyring::{Algorithm, Keyring};
+
+/// JOSE header of access tokens
 #[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    /// Kept as a string so unknown algorithms are told apart from malformed
+    /// headers
+    alg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    typ: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TokenError {
+    Malformed,
+    /// The algorithm of the header isn't allowed
+    Algorithm,
+    /// No allowed key of the algorithm verifies the signature
+    Signature,
+    Expired,
+    NotYetValid,
+    /// The token was issued by or for another service
+    Claims,
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            TokenError::Malformed => "Malformed token",
+            TokenError::Algorithm => "Algorithm not allowed",
+            TokenError::Signature => "Invalid signature",
+            TokenError::Expired => "Expired token",
+            TokenError::NotYetValid => "Token not valid yet",
+            TokenError::Claims => "Token issued for another service",
+        };
+
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenClaims {
     pub sub: String,
     pub username: String,
-    iat: String,
-    exp: String,
+    /// Issuer, absent from tokens issued before it was set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Audience, absent from tokens issued before it was set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    /// Unique id of the token, used to revoke it. Empty for tokens issued
+    /// before revocation was supported
+    #[serde(default)]
+    pub jti: String,
+    /// Session the token was issued for. Empty for tokens issued before
+    /// sessions were recorded
+    #[serde(default)]
+    pub sid: String,
+    /// Unix timestamp the token was issued at
+    #[serde(deserialize_with = "numeric_date")]
+    pub iat: i64,
+    /// Unix timestamp the token is valid from
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "optional_numeric_date"
+    )]
+    pub nbf: Option<i64>,
+    /// Unix timestamp the token expires at
+    #[serde(deserialize_with = "numeric_date")]
+    pub exp: i64,
+}
+
+impl TokenClaims {
+    /// Session the token was issued for
+    pub fn session_id(&self) -> Option<ObjectId> {
+        ObjectId::parse_str(&self.sid).ok()
+    }
+}
+
+/// How access tokens are signed and who they are for
+#[derive(Debug, Clone)]
+pub struct TokenConfig {
+    pub keyring: Keyring,
+    /// Algorithms accepted in the header of tokens
+    pub algorithms: Vec<Algorithm>,
+    pub issuer: String,
+    pub audience: String,
 }
 
 pub struct Token {}
 
 impl Token {
-    pub fn from_user(user: &User, secret_key: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let key: Hmac<Sha256> = Hmac::new_from_slice(secret_key.as_bytes())?;
-
-        let mut claims = BTreeMap::new();
+    pub fn from_user(
+        user: &User,
+        session_id: ObjectId,
+        config: &TokenConfig,
+    ) -> Result<String, Box<dyn std::error::Error>> {
         let timestamp = Utc::now().timestamp();
-        claims.insert("sub", user.clone().id.unwrap().to_string());
-        claims.insert("username", user.clone().name);
-        claims.insert("iat", timestamp.to_string());
-        claims.insert("exp", (timestamp + ONE_DAY_IN_SECONDS).to_string());
+        let claims = TokenClaims {
+            sub: user.id.ok_or("User has no id")?.to_string(),
+            username: user.name.clone(),
+            iss: Some(config.issuer.clone()),
+            aud: Some(config.audience.clone()),
+            jti: ObjectId::new().to_hex(),
+            sid: session_id.to_hex(),
+            iat: timestamp,
+            nbf: Some(timestamp),
+            exp: timestamp + ACCESS_TOKEN_LIFETIME_IN_SECONDS,
+        };
+
+        Self::sign(&claims, &config.keyring)
+    }
 
-        let token = claims.sign_with_key(&key)?;
+    /// Sign the claims with the current key of the keyring
+    pub fn sign(
+        claims: &TokenClaims,
+        keyring: &Keyring,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let key = keyring.signing_key();
+        let header = Header {
+            alg: key.algorithm.as_str().to_string(),
+            kid: Some(key.id.clone()),
+            typ: Some("JWT".to_string()),
+        };
+        let signing_input = format!(
+            "{}.{}",
+            BASE64URL_NOPAD.encode(&serde_json::to_vec(&header)?),
+            BASE64URL_NOPAD.encode(&serde_json::to_vec(claims)?)
+        );
+        let signature = key.sign(signing_input.as_bytes())?;
 
-        Ok(token)
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            BASE64URL_NOPAD.encode(&signature)
+        ))
     }
 
-    pub fn parse(token: &str, secret_key: &str) -> Result<TokenClaims, Box<dyn std::error::Error>> {
-        let key: Hmac<Sha256> = Hmac::new_from_slice(secret_key.as_bytes())?;
+    pub fn parse(token: &str, config: &TokenConfig) -> Result<TokenClaims, TokenError> {
+        let (signing_input, signature) = token.rsplit_once('.').ok_or(TokenError::Malformed)?;
+        let (header, claims) = signing_input.split_once('.').ok_or(TokenError::Malformed)?;
+        let header: Header = decode_json(header)?;
+        let signature = BASE64URL_NOPAD
+            .decode(signature.as_bytes())
+            .map_err(|_| TokenError::Malformed)?;
 
-        let token_data: jwt::Token<Header, TokenClaims, _> =
-            token.verify_with_key(&key).map_err(|e| {
-                log::error!("[Token] Error verifying token: {:?}", e);
-                Box::new(jwt::Error::InvalidSignature)
-            })?;
+        let algorithm = header
+            .alg
+            .parse::<Algorithm>()
+            .ok()
+            .filter(|algorithm| config.algorithms.contains(algorithm))
+            .ok_or(TokenError::Algorithm)?;
 
-        let (_header, claims) = token_data.into();
+        // Only keys of the algorithm are tried, so a public key can't be used
+        // as an HMAC secret. Tokens signed before keys had ids may have been
+        // signed with any of them
+        let is_verified = config
+            .keyring
+            .keys()
+            .iter()
+            .filter(|key| key.algorithm == algorithm)
+            .filter(|key| header.kid.as_ref().is_none_or(|kid| *kid == key.id))
+            .any(|key| key.verify(signing_input.as_bytes(), &signature));
 
-        if claims.exp.parse::<i64>()? < Utc::now().timestamp() {
+        if !is_verified {
+            log::error!("[Token] Error verifying token");
+            return Err(TokenError::Signature);
+        }
+
+        let claims: TokenClaims = decode_json(claims)?;
+        let now = Utc::now().timestamp();
+
+        if claims.exp < now {
             log::info!("[Token] Expired: {}", claims.sub);
-            return Err(Box::new(jwt::Error::InvalidSignature));
+            return Err(TokenError::Expired);
+        }
+
+        if claims.nbf.is_some_and(|nbf| nbf > now) {
+            log::info!("[Token] Not valid yet: {}", claims.sub);
+            return Err(TokenError::NotYetValid);
+        }
+
+        // Tokens issued before these claims were set have neither of them
+        if claims.iss.as_ref().is_some_and(|iss| *iss != config.issuer)
+            || claims
+                .aud
+                .as_ref()
+                .is_some_and(|aud| *aud != config.audience)
+        {
+            log::info!("[Token] Issued for another service: {}", claims.sub);
+            return Err(TokenError::Claims);
         }
 
         Ok(claims)
     }
 }
+
+fn decode_json<T: de::DeserializeOwned>(segment: &str) -> Result<T, TokenError> {
+    let json = BASE64URL_NOPAD
+        .decode(segment.as_bytes())
+        .map_err(|_| TokenError::Malformed)?;
+
+    serde_json::from_slice(&json).map_err(|_| TokenError::Malformed)
+}
+
+/// Deserialize a NumericDate, also accepting the strings older tokens used
+fn numeric_date<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(NumericDateVisitor)
+}
+
+fn optional_numeric_date<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    numeric_date(deserializer).map(Some)
+}
+
+struct NumericDateVisitor;
+
+impl de::Visitor<'_> for NumericDateVisitor {
+    type Value = i64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a unix timestamp as a number or a string")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<i64, E> {
+        Ok(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<i64, E> {
+        i64::try_from(value).map_err(E::custom)
+    }
+
+    // NumericDate may have a fractional part
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<i64, E> {
+        Ok(value as i64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<i64, E> {
+        value.trim().parse().map_err(E::custom)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{
+    auth::password_reset::reset_link,
+    env::state::AppState,
+    models::{password_reset_token::PasswordResetToken, user::User},
+};
+
+/// Run the command given on the command line, if any
+///
+/// Returns whether a command was given, in which case the server shouldn't
+/// start.
+///
+/// - `reset-password <name>`: print a password reset link for the user
+pub async fn run(state: &AppState) -> bool {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+
+    match args.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        [] => false,
+        ["reset-password", name] => {
+            reset_password(state, name).await;
+            true
+        }
+        _ => {
+            eprintln!("Usage: marshallku_blog_backend [reset-password <name>]");
+            std::process::exit(2);
+        }
+    }
+}
+
+async fn reset_password(state: &AppState, name: &str) {
+    let user = match User::find_by_name(&state.db, name).await {
+        Ok(user) => user,
+        Err(e) => {
+            eprintln!("Failed to find user {}: {}", name, e);
+            std::process::exit(1);
+        }
+    };
+
+    match PasswordResetToken::issue(&state.db, user.id.unwrap()).await {
+        Ok(token) => {
+            println!("Password reset for {}, valid for an hour:", user.name);
+            println!("{}", reset_link(&token));
+        }
+        Err(e) => {
+            eprintln!("Failed to issue password reset token: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -1 +1,60 @@
+use super::time::{ONE_DAY_IN_SECONDS, ONE_HOUR_IN_SECONDS, ONE_MINUTE_IN_SECONDS};
+
 pub const TOKEN_COOKIE_KEY: &str = "auth-token";
+pub const REFRESH_TOKEN_COOKIE_KEY: &str = "refresh-token";
+
+/// Realm of the `WWW-Authenticate` challenge
+pub const AUTH_REALM: &str = "marshallku-blog";
+
+pub const ACCESS_TOKEN_LIFETIME_IN_SECONDS: i64 = 15 * ONE_MINUTE_IN_SECONDS;
+pub const REFRESH_TOKEN_LIFETIME_IN_SECONDS: i64 = 30 * ONE_DAY_IN_SECONDS;
+pub const INVITE_LIFETIME_IN_SECONDS: i64 = 7 * ONE_DAY_IN_SECONDS;
+pub const PASSWORD_RESET_TOKEN_LIFETIME_IN_SECONDS: i64 = ONE_HOUR_IN_SECONDS;
+
+/// Failed signins allowed for an account name before it gets locked
+pub const LOGIN_MAX_FAILURES_PER_NAME: i32 = 5;
+/// Failed signins allowed from an IP address before it gets locked, higher
+/// than per name as an address can be shared
+pub const LOGIN_MAX_FAILURES_PER_IP: i32 = 20;
+/// First lockout, doubled for every failure past the limit
+pub const LOGIN_LOCKOUT_BASE_IN_SECONDS: i64 = 30;
+pub const LOGIN_LOCKOUT_MAX_IN_SECONDS: i64 = ONE_HOUR_IN_SECONDS;
+/// How long failures are remembered after the last one
+pub const LOGIN_ATTEMPT_RETENTION_IN_SECONDS: i64 = ONE_DAY_IN_SECONDS;
+
+/// How long the second step of a two-factor signin can take
+pub const TOTP_CHALLENGE_LIFETIME_IN_SECONDS: i64 = 5 * ONE_MINUTE_IN_SECONDS;
+
+/// How long a passkey ceremony can take
+pub const WEBAUTHN_CHALLENGE_LIFETIME_IN_SECONDS: i64 = 5 * ONE_MINUTE_IN_SECONDS;
+
+/// How long the user can take to authorize at an OAuth provider
+pub const OAUTH_STATE_LIFETIME_IN_SECONDS: i64 = 10 * ONE_MINUTE_IN_SECONDS;
+/// Cookie binding the OAuth state to the browser that started the sign in
+pub const OAUTH_STATE_COOKIE_KEY: &str = "oauth-state";
+/// How long the OpenID configuration of a provider is reused
+pub const OAUTH_DISCOVERY_CACHE_LIFETIME_IN_SECONDS: i64 = ONE_HOUR_IN_SECONDS;
+
+/// Cookie binding the IndieAuth state to the browser that started the
+/// verification
+pub const INDIEAUTH_STATE_COOKIE_KEY: &str = "indieauth-state";
+pub const VERIFIED_SITE_COOKIE_KEY: &str = "verified-site";
+/// How long a website verified through IndieAuth is trusted for comments
+pub const VERIFIED_SITE_LIFETIME_IN_SECONDS: i64 = 30 * ONE_DAY_IN_SECONDS;
+
+pub const MAGIC_LINK_LIFETIME_IN_SECONDS: i64 = 15 * ONE_MINUTE_IN_SECONDS;
+/// Magic links that can be requested for an address before it gets locked,
+/// reset once one of them is used
+pub const MAGIC_LINK_MAX_REQUESTS_PER_EMAIL: i32 = 5;
+pub const MAGIC_LINK_MAX_REQUESTS_PER_IP: i32 = 20;
+
+/// How often the last activity of a session is recorded
+pub const SESSION_TOUCH_INTERVAL_IN_SECONDS: i64 = ONE_MINUTE_IN_SECONDS;
+
+pub const CSRF_COOKIE_KEY: &str = "csrf-token";
+/// Header the CSRF token from the cookie has to be echoed in
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// How long the signup creating the Root user can take before another
+/// signup can take over
+pub const BOOTSTRAP_CLAIM_TIMEOUT_IN_SECONDS: i64 = ONE_MINUTE_IN_SECONDS;
@@ -1 +1,3 @@
+pub const ONE_MINUTE_IN_SECONDS: i64 = 60;
+pub const ONE_HOUR_IN_SECONDS: i64 = 3600;
 pub const ONE_DAY_IN_SECONDS: i64 = 86400;
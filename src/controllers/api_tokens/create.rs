@@ -0,0 +1,82 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::api_token::{ApiToken, TokenScope},
+    utils::validator::ValidatedJson,
+};
+
+#[derive(Deserialize, Validate)]
+pub struct CreateApiTokenPayload {
+    #[validate(length(min = 1, max = 64, message = "Name must be 1 to 64 characters"))]
+    pub name: String,
+
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub scopes: Vec<TokenScope>,
+
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub async fn post(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateApiTokenPayload>,
+) -> impl IntoResponse {
+    // API tokens can't be used to mint more API tokens
+    if let Err(rejection) = auth.require_permission(Permission::TokenManage) {
+        return rejection.into_response();
+    }
+
+    if payload
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Expiry must be in the future" })),
+        )
+            .into_response();
+    }
+
+    let mut token_scopes: Vec<TokenScope> = Vec::new();
+
+    for scope in payload.scopes {
+        if !token_scopes.contains(&scope) {
+            token_scopes.push(scope);
+        }
+    }
+
+    let result = ApiToken::create(
+        &state.db,
+        auth.user.id.unwrap(),
+        payload.name,
+        token_scopes,
+        payload.expires_at,
+    )
+    .await;
+
+    match result {
+        Ok((api_token, secret)) => (
+            StatusCode::CREATED,
+            Json(json!({
+                "token": secret,
+                "apiToken": api_token.to_response(),
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to create API token: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to create API token" })),
+            )
+                .into_response()
+        }
+    }
+}
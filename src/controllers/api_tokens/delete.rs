@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::api_token::ApiToken,
+};
+
+pub async fn delete(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::TokenManage) {
+        return rejection.into_response();
+    }
+
+    match ApiToken::revoke(&state.db, auth.user.id.unwrap(), &id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(json!({ "message": "API token revoked successfully" })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to revoke API token: {}", e);
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "API token not found" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::api_token::{ApiToken, ApiTokenResponse},
+};
+
+pub async fn get(auth: AuthUser, State(state): State<AppState>) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::TokenManage) {
+        return rejection.into_response();
+    }
+
+    let api_tokens = ApiToken::find_by_user(&state.db, auth.user.id.unwrap()).await;
+
+    if api_tokens.is_err() {
+        log::error!("Failed to get API tokens: {:?}", api_tokens.err());
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to get API tokens" })),
+        )
+            .into_response();
+    }
+
+    let api_tokens = api_tokens
+        .unwrap()
+        .iter()
+        .map(ApiToken::to_response)
+        .collect::<Vec<ApiTokenResponse>>();
+
+    (StatusCode::OK, Json(api_tokens)).into_response()
+}
@@ -1,30 +1,152 @@
 use axum::{
-    routing::{delete, get, post},
+    middleware::from_fn_with_state,
+    routing::{delete, get, patch, post, put},
     Router,
 };
 
-use crate::env::state::AppState;
+use crate::{
+    env::state::AppState,
+    middleware::{ban, csrf},
+};
 
 pub const API_VERSION_PREFIX: &str = "/api/v2";
 
-pub fn app() -> Router<AppState> {
+pub fn app(state: AppState) -> Router<AppState> {
     Router::new()
         .route("/health", get(super::health::get))
+        .route("/.well-known/jwks.json", get(super::jwks::get))
         .route(
             &format!("{}/auth/signin", API_VERSION_PREFIX),
             post(super::auth::signin::post),
         )
+        .route(
+            &format!("{}/auth/csrf", API_VERSION_PREFIX),
+            get(super::auth::csrf::get),
+        )
+        .route(
+            &format!("{}/auth/invites", API_VERSION_PREFIX),
+            get(super::invites::list::get).post(super::invites::create::post),
+        )
+        .route(
+            &format!("{}/auth/invites/:id", API_VERSION_PREFIX),
+            delete(super::invites::delete::delete),
+        )
+        .route(
+            &format!("{}/auth/indieauth", API_VERSION_PREFIX),
+            get(super::indieauth::start::get),
+        )
+        .route(
+            &format!("{}/auth/indieauth/callback", API_VERSION_PREFIX),
+            get(super::indieauth::callback::get),
+        )
+        .route(
+            &format!("{}/auth/magic-link", API_VERSION_PREFIX),
+            post(super::magic_link::request::post),
+        )
+        .route(
+            &format!("{}/auth/magic-link/verify", API_VERSION_PREFIX),
+            get(super::magic_link::verify::get),
+        )
+        .route(
+            &format!("{}/auth/oauth/:provider", API_VERSION_PREFIX),
+            get(super::oauth::start::get),
+        )
+        .route(
+            &format!("{}/auth/oauth/:provider/callback", API_VERSION_PREFIX),
+            get(super::oauth::callback::get),
+        )
+        .route(
+            &format!("{}/auth/passkey/login/start", API_VERSION_PREFIX),
+            post(super::passkeys::login_start::post),
+        )
+        .route(
+            &format!("{}/auth/passkey/login/finish", API_VERSION_PREFIX),
+            post(super::passkeys::login_finish::post),
+        )
+        .route(
+            &format!("{}/auth/passkey/register/start", API_VERSION_PREFIX),
+            post(super::passkeys::register_start::post),
+        )
+        .route(
+            &format!("{}/auth/passkey/register/finish", API_VERSION_PREFIX),
+            post(super::passkeys::register_finish::post),
+        )
+        .route(
+            &format!("{}/auth/passkeys", API_VERSION_PREFIX),
+            get(super::passkeys::list::get),
+        )
+        .route(
+            &format!("{}/auth/passkeys/:id", API_VERSION_PREFIX),
+            delete(super::passkeys::delete::delete),
+        )
+        .route(
+            &format!("{}/auth/password", API_VERSION_PREFIX),
+            post(super::auth::password::post),
+        )
+        .route(
+            &format!("{}/auth/password/reset", API_VERSION_PREFIX),
+            post(super::auth::password_reset::post),
+        )
+        .route(
+            &format!("{}/auth/password/reset-token", API_VERSION_PREFIX),
+            post(super::auth::reset_token::post),
+        )
+        .route(
+            &format!("{}/auth/refresh", API_VERSION_PREFIX),
+            post(super::auth::refresh::post),
+        )
+        .route(
+            &format!("{}/auth/signout", API_VERSION_PREFIX),
+            post(super::auth::signout::post),
+        )
+        .route(
+            &format!("{}/auth/sessions", API_VERSION_PREFIX),
+            get(super::sessions::list::get).delete(super::sessions::delete_all::delete),
+        )
+        .route(
+            &format!("{}/auth/sessions/:id", API_VERSION_PREFIX),
+            delete(super::sessions::delete::delete),
+        )
+        .route(
+            &format!("{}/auth/signin/totp", API_VERSION_PREFIX),
+            post(super::totp::signin::post),
+        )
         .route(
             &format!("{}/auth/signup", API_VERSION_PREFIX),
-            post(super::auth::signup::post),
+            post(super::auth::signup::post).layer(from_fn_with_state(state.clone(), ban::enforce)),
         )
         .route(
             &format!("{}/auth/status", API_VERSION_PREFIX),
             get(super::auth::status::get),
         )
+        .route(
+            &format!("{}/auth/tokens", API_VERSION_PREFIX),
+            get(super::api_tokens::list::get).post(super::api_tokens::create::post),
+        )
+        .route(
+            &format!("{}/auth/tokens/:id", API_VERSION_PREFIX),
+            delete(super::api_tokens::delete::delete),
+        )
+        .route(
+            &format!("{}/auth/totp/enroll", API_VERSION_PREFIX),
+            post(super::totp::enroll::post),
+        )
+        .route(
+            &format!("{}/auth/totp/confirm", API_VERSION_PREFIX),
+            post(super::totp::confirm::post),
+        )
+        .route(
+            &format!("{}/auth/totp/disable", API_VERSION_PREFIX),
+            post(super::totp::disable::post),
+        )
         .route(
             &format!("{}/comment/create", API_VERSION_PREFIX),
-            post(super::comments::create::post),
+            post(super::comments::create::post)
+                .layer(from_fn_with_state(state.clone(), ban::enforce)),
+        )
+        .route(
+            &format!("{}/comment/form-token", API_VERSION_PREFIX),
+            get(super::comments::form_token::get),
         )
         .route(
             &format!("{}/comment/list", API_VERSION_PREFIX),
@@ -32,7 +154,61 @@ pub fn app() -> Router<AppState> {
         )
         .route(
             &format!("{}/comment/:id", API_VERSION_PREFIX),
-            delete(super::comments::delete::delete),
+            patch(super::comments::update::patch)
+                .layer(from_fn_with_state(state.clone(), ban::enforce))
+                .delete(super::comments::delete::delete),
+        )
+        .route(
+            &format!("{}/comment/:id/fingerprint", API_VERSION_PREFIX),
+            get(super::comments::fingerprint::get),
+        )
+        .route(
+            &format!("{}/comment/tripcode/:tripcode", API_VERSION_PREFIX),
+            get(super::comments::tripcode::get),
+        )
+        .route(
+            &format!("{}/comment/by-fingerprint", API_VERSION_PREFIX),
+            get(super::comments::by_fingerprint::get),
+        )
+        .route(
+            &format!("{}/ban", API_VERSION_PREFIX),
+            get(super::bans::list::get).post(super::bans::create::post),
+        )
+        .route(
+            &format!("{}/ban/:id", API_VERSION_PREFIX),
+            put(super::bans::update::put).delete(super::bans::delete::delete),
+        )
+        .route(
+            &format!("{}/user", API_VERSION_PREFIX),
+            get(super::users::list::get),
+        )
+        .route(
+            &format!("{}/user/:id", API_VERSION_PREFIX),
+            get(super::users::get::get).delete(super::users::delete::delete),
+        )
+        .route(
+            &format!("{}/user/:id/comments", API_VERSION_PREFIX),
+            get(super::users::comments::get),
+        )
+        .route(
+            &format!("{}/user/:id/sessions", API_VERSION_PREFIX),
+            get(super::users::sessions::get),
+        )
+        .route(
+            &format!("{}/user/:id/role", API_VERSION_PREFIX),
+            patch(super::users::update_role::patch),
+        )
+        .route(
+            &format!("{}/user/:id/disable", API_VERSION_PREFIX),
+            post(super::users::disable::post),
+        )
+        .route(
+            &format!("{}/user/:id/enable", API_VERSION_PREFIX),
+            post(super::users::enable::post),
+        )
+        .route(
+            &format!("{}/audit-log", API_VERSION_PREFIX),
+            get(super::audit_log::list::get),
         )
         .route(
             &format!("{}/recent", API_VERSION_PREFIX),
@@ -42,4 +218,5 @@ pub fn app() -> Router<AppState> {
             &format!("{}/thumbnail/*path", API_VERSION_PREFIX),
             get(super::thumbnail::get::get),
         )
+        .layer(from_fn_with_state(state, csrf::enforce))
 }
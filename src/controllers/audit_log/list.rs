@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bson::oid::ObjectId;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::audit_log::{AuditLog, AuditLogResponse},
+};
+
+const MAX_LIMIT: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    /// Page to get, starting from 1
+    #[serde(default = "default_page")]
+    pub page: u64,
+
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+
+    /// Only get entries about this user
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub async fn get(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<AuditLogQuery>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let target_id = match query.user_id.as_deref().map(ObjectId::parse_str) {
+        Some(Ok(target_id)) => Some(target_id),
+        Some(Err(_)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid user id" })),
+            )
+                .into_response();
+        }
+        None => None,
+    };
+    let page = query.page.max(1);
+    let limit = query.limit.clamp(1, MAX_LIMIT);
+
+    match AuditLog::find_page(&state.db, target_id, (page - 1) * limit as u64, limit).await {
+        Ok(audit_logs) => (
+            StatusCode::OK,
+            Json(
+                audit_logs
+                    .iter()
+                    .map(AuditLog::to_response)
+                    .collect::<Vec<AuditLogResponse>>(),
+            ),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to get audit log: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to get audit log" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use axum::{
+    extract::State,
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde_json::json;
+
+use crate::{
+    auth::{cookie::csrf_cookie, csrf, session},
+    env::state::AppState,
+};
+
+/// Issue a CSRF token bound to the session of the cookies, for pages that
+/// can't read the `csrf-token` cookie or signed in before it existed
+pub async fn get(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    let session_id = match session::from_cookies(&state, &CookieJar::from_headers(&headers)).await {
+        Ok(session_id) => session_id,
+        Err(e) => {
+            log::error!("Failed to find session of CSRF token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to issue CSRF token" })),
+            )
+                .into_response();
+        }
+    };
+    let token = csrf::issue(&state.jwt_secret, session_id.as_deref());
+
+    (
+        StatusCode::OK,
+        [(
+            SET_COOKIE,
+            csrf_cookie(token.clone(), state.cookie_domain).to_string(),
+        )],
+        Json(json!({ "token": token })),
+    )
+        .into_response()
+}
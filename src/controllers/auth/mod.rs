@@ -1,3 +1,9 @@
+pub mod csrf;
+pub mod password;
+pub mod password_reset;
+pub mod refresh;
+pub mod reset_token;
 pub mod signin;
+pub mod signout;
 pub mod signup;
 pub mod status;
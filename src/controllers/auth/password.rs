@@ -0,0 +1,122 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{guard::AuthUser, session},
+    constants::auth::LOGIN_MAX_FAILURES_PER_NAME,
+    controllers::auth::signin::{check_lockout, record_failures},
+    env::state::AppState,
+    models::{
+        login_attempt::LoginAttempt, refresh_token::RefreshToken, session::Session, user::User,
+    },
+    utils::{
+        client::ClientInfo,
+        encryption::{hash_password, verify_password},
+        validator::ValidatedJson,
+    },
+};
+
+#[derive(Deserialize, Validate)]
+pub struct ChangePasswordPayload {
+    #[serde(rename = "currentPassword")]
+    #[validate(length(min = 1, message = "Current password cannot be empty"))]
+    pub current_password: String,
+
+    #[serde(rename = "newPassword")]
+    #[validate(length(min = 1, message = "New password cannot be empty"))]
+    pub new_password: String,
+}
+
+/// Change the password, signing out every other session
+pub async fn post(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+    client: ClientInfo,
+    ValidatedJson(payload): ValidatedJson<ChangePasswordPayload>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't change the password" })),
+        )
+            .into_response();
+    }
+
+    let user_id = user.id.unwrap();
+    // Guessed current passwords are limited like signins
+    let attempt_key = LoginAttempt::password_change_key(&user_id);
+    let attempt_keys = [(attempt_key.clone(), LOGIN_MAX_FAILURES_PER_NAME)];
+
+    if let Some(response) = check_lockout(&state, &attempt_keys).await {
+        return response;
+    }
+
+    if !verify_password(&payload.current_password, &user.password).unwrap_or(false) {
+        record_failures(&state, &attempt_keys).await;
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "message": "Current password is incorrect" })),
+        )
+            .into_response();
+    }
+
+    if let Err(message) = state
+        .password_policy
+        .check(&payload.new_password, &user.name)
+    {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "message": message }))).into_response();
+    }
+
+    let hashed_password = match hash_password(&payload.new_password) {
+        Ok(hashed_password) => hashed_password,
+        Err(e) => {
+            log::error!("Failed to hash password: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to hash password" })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Err(e) = User::update_password(&state.db, user_id, &hashed_password).await {
+        log::error!("Failed to update password: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to change password" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = LoginAttempt::clear(&state.db, &[attempt_key]).await {
+        log::error!("Failed to clear login attempts: {}", e);
+    }
+
+    if let Err(e) = RefreshToken::revoke_user(&state.db, user_id).await {
+        log::error!("Failed to revoke refresh tokens: {}", e);
+    }
+
+    if let Err(e) = Session::revoke_user(&state.db, user_id).await {
+        log::error!("Failed to revoke sessions: {}", e);
+    }
+
+    // Keep the current session signed in with fresh tokens
+    match session::issue(&state, &user, &client).await {
+        Ok(headers) => (
+            StatusCode::OK,
+            headers,
+            Json(json!({ "message": "Password changed successfully" })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to issue tokens: {:?}", e);
+            (
+                StatusCode::OK,
+                Json(json!({ "message": "Password changed, please sign in again" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    env::state::AppState,
+    models::{
+        password_reset_token::PasswordResetToken, refresh_token::RefreshToken, session::Session,
+        user::User,
+    },
+    utils::{encryption::hash_password, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct ResetPasswordPayload {
+    #[validate(length(min = 1, message = "Token cannot be empty"))]
+    pub token: String,
+
+    #[serde(rename = "newPassword")]
+    #[validate(length(min = 1, message = "New password cannot be empty"))]
+    pub new_password: String,
+}
+
+/// Set a new password with a reset token, signing out every session
+pub async fn post(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<ResetPasswordPayload>,
+) -> impl IntoResponse {
+    let user = match PasswordResetToken::find_active(&state.db, &payload.token).await {
+        Ok(Some(reset_token)) => User::find_by_id(&state.db, &reset_token.user_id.to_string())
+            .await
+            .ok(),
+        Ok(None) => None,
+        Err(e) => {
+            log::error!("Failed to find password reset token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to reset password" })),
+            );
+        }
+    };
+
+    let user = match user {
+        Some(user) => user,
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid or expired reset token" })),
+            );
+        }
+    };
+
+    if let Err(message) = state
+        .password_policy
+        .check(&payload.new_password, &user.name)
+    {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "message": message })));
+    }
+
+    // Consume the token only now, so a rejected password doesn't spend it
+    let user_id = match PasswordResetToken::consume(&state.db, &payload.token).await {
+        Ok(Some(user_id)) => user_id,
+        Ok(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid or expired reset token" })),
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to consume password reset token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to reset password" })),
+            );
+        }
+    };
+
+    let hashed_password = match hash_password(&payload.new_password) {
+        Ok(hashed_password) => hashed_password,
+        Err(e) => {
+            log::error!("Failed to hash password: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to hash password" })),
+            );
+        }
+    };
+
+    if let Err(e) = User::update_password(&state.db, user_id, &hashed_password).await {
+        log::error!("Failed to update password: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to reset password" })),
+        );
+    }
+
+    if let Err(e) = RefreshToken::revoke_user(&state.db, user_id).await {
+        log::error!("Failed to revoke refresh tokens: {}", e);
+    }
+
+    if let Err(e) = Session::revoke_user(&state.db, user_id).await {
+        log::error!("Failed to revoke sessions: {}", e);
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({ "message": "Password reset successfully" })),
+    )
+}
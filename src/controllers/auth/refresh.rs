@@ -0,0 +1,132 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::cookie::CookieJar;
+use serde_json::json;
+
+use crate::{
+    auth::{
+        session::{client_ip_hash, cookie_headers},
+        token::Token,
+    },
+    constants::auth::REFRESH_TOKEN_COOKIE_KEY,
+    env::state::AppState,
+    models::{
+        refresh_token::{RefreshResult, RefreshToken},
+        session::Session,
+        user::User,
+    },
+    utils::client::ClientInfo,
+};
+
+pub async fn post(
+    State(state): State<AppState>,
+    client: ClientInfo,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let cookie_jar = CookieJar::from_headers(&headers);
+    let refresh_token = match cookie_jar.get(REFRESH_TOKEN_COOKIE_KEY) {
+        Some(cookie) => cookie.value().to_string(),
+        None => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Missing refresh-token cookie" })),
+            )
+                .into_response();
+        }
+    };
+
+    let (user_id, family_id, refresh_token) =
+        match RefreshToken::rotate(&state.db, &refresh_token).await {
+            Ok(RefreshResult::Rotated(user_id, family_id, refresh_token)) => {
+                (user_id, family_id, refresh_token)
+            }
+            Ok(RefreshResult::Reused) | Ok(RefreshResult::Invalid) => {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({ "message": "Invalid refresh-token cookie" })),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                log::error!("Failed to rotate refresh token: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "message": "Failed to refresh token" })),
+                )
+                    .into_response();
+            }
+        };
+
+    // The refresh token family is the session
+    match Session::resume(
+        &state.db,
+        family_id,
+        user_id,
+        client.user_agent.clone(),
+        client_ip_hash(&state, &client),
+    )
+    .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            if let Err(e) = RefreshToken::revoke_family(&state.db, family_id).await {
+                log::error!("Failed to revoke refresh token family: {}", e);
+            }
+
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Session was revoked" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to resume session: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to refresh token" })),
+            )
+                .into_response();
+        }
+    }
+
+    let user = match User::find_by_id(&state.db, &user_id.to_string()).await {
+        Ok(user) if user.is_disabled() => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(json!({ "message": "This account is disabled" })),
+            )
+                .into_response();
+        }
+        Ok(user) => user,
+        Err(_) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid refresh-token cookie" })),
+            )
+                .into_response();
+        }
+    };
+
+    let access_token = match Token::from_user(&user, family_id, &state.token_config) {
+        Ok(access_token) => access_token,
+        Err(e) => {
+            log::error!("Failed to generate token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to generate token" })),
+            )
+                .into_response();
+        }
+    };
+
+    (
+        StatusCode::OK,
+        cookie_headers(&state, access_token, refresh_token, family_id),
+        Json(json!({ "message": "Token refreshed" })),
+    )
+        .into_response()
+}
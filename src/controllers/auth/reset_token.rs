@@ -0,0 +1,92 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{guard::AuthUser, password_reset::reset_link, permission::Permission},
+    env::state::AppState,
+    models::{password_reset_token::PasswordResetToken, user::User},
+    utils::{mailer, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct CreateResetTokenPayload {
+    #[validate(length(min = 1, message = "Name cannot be empty"))]
+    pub name: String,
+
+    /// Address to mail the reset link to, the link is returned when omitted
+    #[validate(email(message = "Invalid email format"))]
+    pub email: Option<String>,
+}
+
+/// Issue a password reset token for a user
+pub async fn post(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateResetTokenPayload>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    if payload.email.is_some() && !mailer::is_configured() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "message": "Mail delivery is not configured" })),
+        )
+            .into_response();
+    }
+
+    let target = match User::find_by_name(&state.db, &payload.name).await {
+        Ok(target) => target,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "User not found" })),
+            )
+                .into_response();
+        }
+    };
+
+    let token = match PasswordResetToken::issue(&state.db, target.id.unwrap()).await {
+        Ok(token) => token,
+        Err(e) => {
+            log::error!("Failed to issue password reset token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to issue password reset token" })),
+            )
+                .into_response();
+        }
+    };
+    let link = reset_link(&token);
+
+    let email = match payload.email {
+        Some(email) => email,
+        None => {
+            return (StatusCode::CREATED, Json(json!({ "resetLink": link }))).into_response();
+        }
+    };
+
+    let body = format!(
+        "A password reset was requested for {}.\n\nUse the following to set a new password, it expires in an hour:\n\n{}\n",
+        target.name, link
+    );
+
+    match mailer::send_mail(&email, "Reset your password", body).await {
+        Ok(_) => (
+            StatusCode::CREATED,
+            Json(json!({ "message": "Password reset link sent" })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to send password reset mail: {}", e);
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({ "message": "Failed to send password reset mail" })),
+            )
+                .into_response()
+        }
+    }
+}
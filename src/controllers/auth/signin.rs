@@ -1,18 +1,23 @@
 use axum::{
     extract::State,
-    http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    http::{header::RETRY_AFTER, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use cookie::{Cookie, SameSite};
-use reqwest::header::SET_COOKIE;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde_json::json;
-use time::Duration;
 
 use crate::{
-    auth::token::Token, constants::auth::TOKEN_COOKIE_KEY, env::state::AppState,
-    models::user::User, utils::encryption::verify_password,
+    auth::{challenge, session},
+    constants::auth::{LOGIN_MAX_FAILURES_PER_IP, LOGIN_MAX_FAILURES_PER_NAME},
+    env::state::AppState,
+    models::{login_attempt::LoginAttempt, user::User},
+    utils::{
+        client::ClientInfo,
+        encryption::{hash_ip, verify_dummy_password, verify_password},
+        webhook::{send_message, DiscordEmbed, DiscordField},
+    },
 };
 
 #[derive(Deserialize)]
@@ -22,31 +27,145 @@ pub struct SignInPayload {
 }
 
 pub async fn post(
+    client: ClientInfo,
     State(state): State<AppState>,
     Json(payload): Json<SignInPayload>,
 ) -> impl IntoResponse {
-    let user = User::find_by_name(&state.db, &payload.name).await;
+    let attempt_keys = attempt_keys(&state, &payload.name, &client);
 
-    if user.is_err() {
+    if let Some(response) = check_lockout(&state, &attempt_keys).await {
+        return response;
+    }
+
+    // Check a password even for unknown names, so the response time doesn't
+    // tell whether the name exists
+    let user = User::find_by_name(&state.db, &payload.name).await.ok();
+    // Users without a usable password hash, such as passwordless ones whose
+    // hash is empty, take as long to refuse as the others
+    let is_valid = match &user {
+        Some(user) if !user.password.is_empty() => {
+            verify_password(&payload.password, &user.password)
+                .unwrap_or_else(|_| verify_dummy_password(&payload.password))
+        }
+        _ => verify_dummy_password(&payload.password),
+    };
+
+    let user = match user.filter(|_| is_valid) {
+        Some(user) => user,
+        None => {
+            record_failures(&state, &attempt_keys).await;
+
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid name or password" })),
+            )
+                .into_response();
+        }
+    };
+
+    // Failures are kept until the second step passes, so the password step
+    // can't be used to reset the count of wrong codes
+    if user.is_totp_enabled() {
         return (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({ "message": "Invalid name or password" })),
+            StatusCode::OK,
+            Json(json!({
+                "message": "Two-factor authentication required",
+                "challenge": challenge::issue(&user.id.unwrap().to_hex(), &state.jwt_secret),
+            })),
         )
             .into_response();
     }
 
-    let user = user.unwrap();
-    if !verify_password(&payload.password, &user.password).unwrap() {
+    complete(&state, &user, &client, &attempt_keys).await
+}
+
+/// Keys failed attempts of the request are counted against, with their limit
+pub fn attempt_keys(state: &AppState, name: &str, client: &ClientInfo) -> Vec<(String, i32)> {
+    let mut keys = vec![(LoginAttempt::name_key(name), LOGIN_MAX_FAILURES_PER_NAME)];
+
+    if let Some(ip) = &client.ip {
+        keys.push((
+            LoginAttempt::ip_key(&hash_ip(ip, &state.ip_hash_salt)),
+            LOGIN_MAX_FAILURES_PER_IP,
+        ));
+    }
+
+    keys
+}
+
+/// Response refusing the signin if one of the keys is locked
+pub async fn check_lockout(state: &AppState, attempt_keys: &[(String, i32)]) -> Option<Response> {
+    let keys = attempt_keys
+        .iter()
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<String>>();
+
+    match LoginAttempt::locked_until(&state.db, &keys).await {
+        Ok(None) => None,
+        Ok(Some(locked_until)) => {
+            let retry_after = (locked_until - Utc::now()).num_seconds().max(1);
+
+            Some(
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(RETRY_AFTER, retry_after.to_string())],
+                    Json(json!({ "message": "Too many failed attempts, please try again later" })),
+                )
+                    .into_response(),
+            )
+        }
+        Err(e) => {
+            log::error!("Failed to check login attempts: {}", e);
+            Some(
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "message": "Failed to sign in" })),
+                )
+                    .into_response(),
+            )
+        }
+    }
+}
+
+/// Count a failed signin against every key
+pub async fn record_failures(state: &AppState, attempt_keys: &[(String, i32)]) {
+    for (key, max_failures) in attempt_keys {
+        match LoginAttempt::record_failure(&state.db, key, *max_failures).await {
+            Ok(Some(locked_until)) => report_lockout(key, locked_until),
+            Ok(None) => {}
+            Err(e) => log::error!("Failed to record login attempt: {}", e),
+        }
+    }
+}
+
+/// Sign the user in once every step passed
+pub async fn complete(
+    state: &AppState,
+    user: &User,
+    client: &ClientInfo,
+    attempt_keys: &[(String, i32)],
+) -> Response {
+    if user.is_disabled() {
         return (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({ "message": "Invalid name or password" })),
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "This account is disabled" })),
         )
             .into_response();
     }
 
-    let token = Token::from_user(&user, &state.jwt_secret);
+    let keys = attempt_keys
+        .iter()
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<String>>();
+
+    if let Err(e) = LoginAttempt::clear(&state.db, &keys).await {
+        log::error!("Failed to clear login attempts: {}", e);
+    }
+
+    let headers = session::issue(state, user, client).await;
 
-    if token.is_err() {
+    if headers.is_err() {
+        log::error!("Failed to issue tokens: {:?}", headers.err());
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({ "message": "Failed to generate token" })),
@@ -54,23 +173,32 @@ pub async fn post(
             .into_response();
     }
 
-    let token = token.unwrap();
-
-    let cookie = Cookie::build((TOKEN_COOKIE_KEY, token))
-        .path("/")
-        .secure(true)
-        .http_only(true)
-        .max_age(Duration::days(1))
-        .same_site(SameSite::None)
-        .domain(state.cookie_domain);
-
-    let mut headers = HeaderMap::new();
-    headers.insert(SET_COOKIE, cookie.to_string().parse().unwrap());
-
     (
         StatusCode::OK,
-        headers,
+        headers.unwrap(),
         Json(json!({ "message": "Login successful" })),
     )
         .into_response()
 }
+
+fn report_lockout(key: &str, locked_until: DateTime<Utc>) {
+    log::warn!("[Auth] Locked {} until {}", key, locked_until);
+
+    send_message(DiscordEmbed {
+        embed_type: "rich".to_string(),
+        title: "Sign-in locked".to_string(),
+        description: "Too many failed sign-in attempts".to_string(),
+        color: None,
+        fields: vec![
+            DiscordField {
+                name: "Key".to_string(),
+                value: key.to_string(),
+            },
+            DiscordField {
+                name: "Locked until".to_string(),
+                value: locked_until.to_rfc3339(),
+            },
+        ],
+        footer: None,
+    });
+}
@@ -0,0 +1,77 @@
+use axum::{
+    extract::State,
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use axum_extra::extract::cookie::CookieJar;
+use bson::oid::ObjectId;
+use chrono::DateTime;
+use serde_json::json;
+
+use crate::{
+    auth::{cookie::removal_cookies, guard::extract_token, token::Token},
+    constants::auth::REFRESH_TOKEN_COOKIE_KEY,
+    env::state::AppState,
+    models::{refresh_token::RefreshToken, revoked_token::RevokedToken, session::Session},
+};
+
+pub async fn post(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    let cookie_jar = CookieJar::from_headers(&headers);
+
+    // Expired or invalid tokens are useless anyway, only revoke valid ones
+    let claims = extract_token(&headers, &state.auth_token_precedence)
+        .and_then(|(_, token)| Token::parse(&token, &state.token_config).ok());
+
+    if let Some(claims) = claims.as_ref().filter(|claims| !claims.jti.is_empty()) {
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).unwrap_or_default();
+
+        if let Err(e) = RevokedToken::revoke(&state.db, &claims.jti, expires_at).await {
+            log::error!("Failed to revoke token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign out" })),
+            )
+                .into_response();
+        }
+    }
+
+    if let Some(claims) = claims {
+        if let Some(session_id) = claims.session_id() {
+            let user_id = ObjectId::parse_str(&claims.sub).unwrap_or_default();
+
+            if let Err(e) = Session::revoke(&state.db, user_id, session_id).await {
+                log::error!("Failed to revoke session: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "message": "Failed to sign out" })),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    if let Some(cookie) = cookie_jar.get(REFRESH_TOKEN_COOKIE_KEY) {
+        if let Err(e) = RefreshToken::revoke_by_token(&state.db, cookie.value()).await {
+            log::error!("Failed to revoke refresh token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign out" })),
+            )
+                .into_response();
+        }
+    }
+
+    let mut headers = HeaderMap::new();
+
+    for cookie in removal_cookies(state.cookie_domain) {
+        headers.append(SET_COOKIE, cookie.to_string().parse().unwrap());
+    }
+
+    (
+        StatusCode::OK,
+        headers,
+        Json(json!({ "message": "Logout successful" })),
+    )
+        .into_response()
+}
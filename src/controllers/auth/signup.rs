@@ -1,27 +1,143 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
 use chrono::Utc;
 use serde::Deserialize;
 use serde_json::json;
+use unicode_normalization::UnicodeNormalization;
+use validator::{Validate, ValidationError};
 
 use crate::{
+    auth::signup::SignupMode,
+    database::is_duplicate_key_error,
     env::state::AppState,
-    models::user::{User, UserRole},
-    utils::encryption::hash_password,
+    models::{
+        bootstrap::Bootstrap,
+        invite::Invite,
+        user::{User, UserRole},
+    },
+    utils::{
+        confusable::is_invisible,
+        encryption::{generate_token, hash_password},
+        validator::ValidatedJson,
+    },
 };
 
-#[derive(Deserialize)]
+/// Attempts at finding a free name for a passwordless user before giving up
+const MAX_NAME_ATTEMPTS: usize = 5;
+
+#[derive(Deserialize, Validate)]
 pub struct SignUpPayload {
+    #[validate(
+        length(min = 2, max = 32, message = "Name must be 2 to 32 characters"),
+        custom(function = "validate_name")
+    )]
     pub name: String,
+
+    #[validate(length(min = 1, message = "Password cannot be empty"))]
     pub password: String,
+
+    /// Code of an invite, required when `SIGNUP_MODE` is `invite`
+    #[serde(rename = "inviteCode")]
+    pub invite_code: Option<String>,
+}
+
+/// Names are made of letters, digits, `_`, `-` and `.`, in their NFKC form
+fn validate_name(name: &str) -> Result<(), ValidationError> {
+    let is_valid_char =
+        |c: char| (c.is_alphanumeric() && !is_invisible(c)) || matches!(c, '_' | '-' | '.');
+
+    if !name.chars().all(is_valid_char) || name.nfkc().collect::<String>() != name {
+        return Err(ValidationError::new("name_charset").with_message(
+            "Name can only contain letters, digits, underscores, hyphens and periods".into(),
+        ));
+    }
+
+    Ok(())
 }
 
 pub async fn post(
     State(app_state): State<AppState>,
-    Json(payload): Json<SignUpPayload>,
+    ValidatedJson(payload): ValidatedJson<SignUpPayload>,
 ) -> impl IntoResponse {
+    if let Err(message) = app_state
+        .password_policy
+        .check(&payload.password, &payload.name)
+    {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "message": message })));
+    }
+
+    let is_first_user = match claim_root(&app_state).await {
+        Ok(is_first_user) => is_first_user,
+        Err(e) => {
+            log::error!("Failed to check for the first user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to create user" })),
+            );
+        }
+    };
+
+    if !is_first_user && app_state.signup_mode == SignupMode::Closed {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "Signup is closed" })),
+        );
+    }
+
+    // The first user is free to take a protected name, being its owner
+    if !is_first_user
+        && app_state
+            .user_names
+            .is_reserved(&payload.name, None, &app_state.protected_names)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "This name is reserved or too similar to another user's" })),
+        );
+    }
+
+    let invite = match payload.invite_code.as_deref().filter(|_| !is_first_user) {
+        Some(code) => match Invite::redeem(&app_state.db, code).await {
+            Ok(Some(invite)) => Some(invite),
+            Ok(None) => {
+                return (
+                    StatusCode::FORBIDDEN,
+                    Json(json!({ "message": "Invalid or expired invite code" })),
+                );
+            }
+            Err(e) => {
+                log::error!("Failed to redeem invite: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "message": "Failed to create user" })),
+                );
+            }
+        },
+        None if !is_first_user && app_state.signup_mode == SignupMode::Invite => {
+            return (
+                StatusCode::FORBIDDEN,
+                Json(json!({ "message": "An invite code is required to sign up" })),
+            );
+        }
+        None => None,
+    };
+
+    // The first account bootstraps the installation
+    let role = match &invite {
+        _ if is_first_user => UserRole::Root,
+        Some(invite) => invite.role.clone(),
+        None => UserRole::User,
+    };
+
     let hashed_password = hash_password(&payload.password);
 
     if hashed_password.is_err() {
+        release_invite(&app_state, invite.as_ref()).await;
+        release_root(&app_state, is_first_user).await;
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(json!({ "message": "Failed to hash password" })),
@@ -30,23 +146,49 @@ pub async fn post(
 
     let user = User {
         name: payload.name,
+        email: None,
         password: hashed_password.unwrap(),
-        role: UserRole::User,
+        role,
+        sessions_valid_after: None,
+        totp: None,
+        disabled_at: None,
         id: None,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
 
-    let user = User::create(&app_state.db, user).await;
+    let user = match User::create(&app_state.db, user).await {
+        Ok(user) => user,
+        Err(e) if is_duplicate_key_error(&e) => {
+            release_invite(&app_state, invite.as_ref()).await;
+            release_root(&app_state, is_first_user).await;
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({ "message": "This name is already taken" })),
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to create user: {}", e);
+            release_invite(&app_state, invite.as_ref()).await;
+            release_root(&app_state, is_first_user).await;
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to create user" })),
+            );
+        }
+    };
 
-    if user.is_err() {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "message": "Failed to create user" })),
-        );
+    if is_first_user {
+        complete_root(&app_state, &user).await;
     }
 
-    let user = user.unwrap();
+    app_state.user_names.insert(&user.name);
+
+    if let Some(invite) = invite {
+        if let Err(e) = Invite::assign(&app_state.db, invite.id.unwrap(), user.id.unwrap()).await {
+            log::error!("Failed to record invite usage: {}", e);
+        }
+    }
 
     (
         StatusCode::CREATED,
@@ -58,3 +200,153 @@ pub async fn post(
         })),
     )
 }
+
+/// Whether the signup creates the first user, who becomes Root
+///
+/// Only one signup can claim it, even when several run on an empty
+/// installation at once.
+async fn claim_root(state: &AppState) -> Result<bool, mongodb::error::Error> {
+    if !User::is_empty(&state.db).await? {
+        return Ok(false);
+    }
+
+    Bootstrap::claim_root(&state.db).await
+}
+
+/// Record the Root user once created
+async fn complete_root(state: &AppState, user: &User) {
+    if let Err(e) = Bootstrap::complete_root(&state.db, user.id.unwrap()).await {
+        log::error!("Failed to record the root user: {}", e);
+    }
+}
+
+/// Let another signup create the Root user after a failed one
+async fn release_root(state: &AppState, is_first_user: bool) {
+    if !is_first_user {
+        return;
+    }
+
+    if let Err(e) = Bootstrap::release_root(&state.db).await {
+        log::error!("Failed to release the root user claim: {}", e);
+    }
+}
+
+/// Let the invite be used again after a failed signup
+async fn release_invite(app_state: &AppState, invite: Option<&Invite>) {
+    if let Some(invite) = invite {
+        if let Err(e) = Invite::release(&app_state.db, invite.id.unwrap()).await {
+            log::error!("Failed to release invite: {}", e);
+        }
+    }
+}
+
+/// Create a user signing in without a password, through an OAuth provider
+/// or a magic link, named after `name_hint`
+///
+/// Only allowed when signup is open, as there's no invite to redeem, and
+/// once the Root user exists. The user can set a password later through a
+/// reset link.
+pub async fn create_passwordless(
+    state: &AppState,
+    name_hint: &str,
+    email: Option<&str>,
+) -> Result<User, Response> {
+    let failure = || {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to create user" })),
+        )
+            .into_response()
+    };
+
+    // The Root user is only created through a password signup, so the first
+    // account is never whoever signs in through a provider first
+    let is_empty = User::is_empty(&state.db).await.map_err(|e| {
+        log::error!("Failed to check for the first user: {}", e);
+        failure()
+    })?;
+
+    if is_empty {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "The first account must sign up with a password" })),
+        )
+            .into_response());
+    }
+
+    if state.signup_mode != SignupMode::Open {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "Signup is closed" })),
+        )
+            .into_response());
+    }
+
+    let password = match hash_password(&generate_token()) {
+        Ok(password) => password,
+        Err(e) => {
+            log::error!("Failed to hash password: {}", e);
+            return Err(failure());
+        }
+    };
+    let base_name = sanitize_name(name_hint);
+
+    for attempt in 0..MAX_NAME_ATTEMPTS {
+        let name = match attempt {
+            0 => base_name.clone(),
+            _ => format!("{}-{}", base_name, rand::random_range(1000..10000)),
+        };
+
+        if state
+            .user_names
+            .is_reserved(&name, None, &state.protected_names)
+        {
+            continue;
+        }
+
+        let user = User {
+            name,
+            email: email.map(str::to_lowercase),
+            password: password.clone(),
+            role: UserRole::User,
+            sessions_valid_after: None,
+            totp: None,
+            disabled_at: None,
+            id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        match User::create(&state.db, user).await {
+            Ok(user) => {
+                state.user_names.insert(&user.name);
+
+                return Ok(user);
+            }
+            Err(e) if is_duplicate_key_error(&e) => continue,
+            Err(e) => {
+                log::error!("Failed to create user: {}", e);
+                return Err(failure());
+            }
+        }
+    }
+
+    log::error!("Failed to find a free name for {}", base_name);
+    Err(failure())
+}
+
+/// Turn a name from elsewhere into one a signup would accept
+fn sanitize_name(name: &str) -> String {
+    let name = name
+        .nfkc()
+        .map(|c| if c.is_whitespace() { '-' } else { c })
+        .filter(|c| (c.is_alphanumeric() && !is_invisible(*c)) || matches!(c, '_' | '-' | '.'))
+        .take(24)
+        .collect::<String>();
+
+    if name.chars().count() < 2 {
+        return "user".to_string();
+    }
+
+    name
+}
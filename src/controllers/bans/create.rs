@@ -0,0 +1,76 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::ban::{Ban, BanKind},
+    utils::validator::ValidatedJson,
+};
+
+#[derive(Deserialize, Validate)]
+pub struct BanPayload {
+    pub kind: BanKind,
+
+    #[validate(length(min = 1, message = "Value cannot be empty"))]
+    pub value: String,
+
+    #[serde(default)]
+    pub shadow: bool,
+
+    #[serde(default)]
+    pub reason: String,
+
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub async fn post(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<BanPayload>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::BanManage) {
+        return rejection.into_response();
+    }
+
+    if !payload.kind.is_valid_value(&payload.value) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Invalid value for {:?} ban", payload.kind) })),
+        )
+            .into_response();
+    }
+
+    let ban = Ban {
+        id: None,
+        kind: payload.kind,
+        value: payload.value.trim().to_string(),
+        shadow: payload.shadow,
+        reason: payload.reason,
+        expires_at: payload.expires_at,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+
+    let ban = match Ban::create(&state.db, ban).await {
+        Ok(ban) => ban,
+        Err(e) => {
+            log::error!("Failed to create ban: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to create ban" })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Err(e) = state.bans.reload(&state.db).await {
+        log::error!("Failed to reload bans: {}", e);
+    }
+
+    (StatusCode::CREATED, Json(ban.to_response())).into_response()
+}
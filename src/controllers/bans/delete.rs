@@ -0,0 +1,42 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::ban::Ban,
+};
+
+pub async fn delete(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::BanManage) {
+        return rejection.into_response();
+    }
+
+    if let Err(e) = Ban::delete(&state.db, &id).await {
+        log::error!("Failed to delete ban: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to delete ban" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = state.bans.reload(&state.db).await {
+        log::error!("Failed to reload bans: {}", e);
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({ "message": "Ban deleted successfully" })),
+    )
+        .into_response()
+}
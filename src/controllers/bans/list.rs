@@ -0,0 +1,33 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::ban::{Ban, BanResponse},
+};
+
+pub async fn get(auth: AuthUser, State(state): State<AppState>) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::BanManage) {
+        return rejection.into_response();
+    }
+
+    let bans = Ban::find_all(&state.db).await;
+
+    if bans.is_err() {
+        log::error!("Failed to get bans: {:?}", bans.err());
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to get bans" })),
+        )
+            .into_response();
+    }
+
+    let bans = bans
+        .unwrap()
+        .iter()
+        .map(Ban::to_response)
+        .collect::<Vec<BanResponse>>();
+
+    (StatusCode::OK, Json(bans)).into_response()
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::ban::Ban,
+    utils::validator::ValidatedJson,
+};
+
+use super::create::BanPayload;
+
+pub async fn put(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<BanPayload>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::BanManage) {
+        return rejection.into_response();
+    }
+
+    if !payload.kind.is_valid_value(&payload.value) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": format!("Invalid value for {:?} ban", payload.kind) })),
+        )
+            .into_response();
+    }
+
+    let ban = match Ban::find_by_id(&state.db, &id).await {
+        Ok(ban) => ban,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Ban not found" })),
+            )
+                .into_response();
+        }
+    };
+    let ban = Ban {
+        kind: payload.kind,
+        value: payload.value.trim().to_string(),
+        shadow: payload.shadow,
+        reason: payload.reason,
+        expires_at: payload.expires_at,
+        updated_at: Utc::now(),
+        ..ban
+    };
+
+    let ban = match Ban::update(&state.db, &id, ban).await {
+        Ok(ban) => ban,
+        Err(e) => {
+            log::error!("Failed to update ban: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to update ban" })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Err(e) = state.bans.reload(&state.db).await {
+        log::error!("Failed to reload bans: {}", e);
+    }
+
+    (StatusCode::OK, Json(ban.to_response())).into_response()
+}
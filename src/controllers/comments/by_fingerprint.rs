@@ -0,0 +1,57 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::comment::Comment,
+};
+
+#[derive(Deserialize)]
+pub struct ByFingerprintQuery {
+    #[serde(rename = "ipHash")]
+    pub ip_hash: String,
+}
+
+pub async fn get(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<ByFingerprintQuery>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::CommentRead) {
+        return rejection.into_response();
+    }
+
+    let comments = Comment::find_by_ip_hash(&state.db, &query.ip_hash).await;
+
+    if comments.is_err() {
+        log::error!("Failed to get comments: {:?}", comments.err());
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to get comments" })),
+        )
+            .into_response();
+    }
+
+    let comments = comments
+        .unwrap()
+        .into_iter()
+        .map(|comment| {
+            let moderation = comment.moderation.clone().unwrap_or_default();
+
+            json!({
+                "comment": comment.to_response(),
+                "ipHash": moderation.ip_hash,
+                "userAgent": moderation.user_agent,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    (StatusCode::OK, Json(comments)).into_response()
+}
@@ -1,20 +1,42 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use axum_extra::extract::CookieJar;
 use bson::oid::ObjectId;
-use chrono::Utc;
+use chrono::{Duration, Utc};
+use data_encoding::HEXLOWER;
 use serde::Deserialize;
-use serde_json::json;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use validator::Validate;
 
 use crate::{
-    auth::guard::AuthUserOrPublic,
+    auth::{guard::AuthUserOrPublic, indieauth},
+    constants::auth::VERIFIED_SITE_COOKIE_KEY,
     env::state::AppState,
-    models::{comment::Comment, user::UserRole},
+    middleware::ban::ShadowBanned,
+    models::{
+        api_token::TokenScope,
+        comment::{Comment, CommentModeration},
+        comment_digest::CommentDigest,
+        idempotency_key::{IdempotencyClaim, IdempotencyKey},
+        user::UserRole,
+    },
     utils::{
+        client::ClientInfo,
+        encryption::hash_ip,
+        form_token, tripcode,
         validator::ValidatedJson,
         webhook::{send_message, DiscordEmbed, DiscordField},
     },
 };
 
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
 #[derive(Deserialize, Validate)]
 pub struct AddCommentPayload {
     #[serde(rename = "postSlug")]
@@ -35,31 +57,296 @@ pub struct AddCommentPayload {
 
     #[serde(rename = "parentCommentId")]
     pub parent_comment_id: Option<String>,
+
+    /// Hidden field that humans never fill in
+    #[serde(default)]
+    pub honeypot: Option<String>,
+
+    /// Signed token issued when the comment form was rendered
+    #[serde(rename = "formToken")]
+    pub form_token: Option<String>,
+
+    /// Secret of an anonymous commenter, used to derive their tripcode
+    #[serde(rename = "tripcodeSecret")]
+    #[validate(length(min = 8, message = "Tripcode secret must be at least 8 characters"))]
+    pub tripcode_secret: Option<String>,
+}
+
+/// Whether the submission looks like it came from a bot.
+///
+/// A filled honeypot, an invalid form token, or a form submitted faster than
+/// `COMMENT_MIN_FILL_SECONDS` after it was rendered, is treated as spam.
+/// Submissions without a form token count as spam too, unless
+/// `COMMENT_REQUIRE_FORM_TOKEN` is turned off for older clients that don't
+/// send one.
+fn is_likely_spam(payload: &AddCommentPayload, state: &AppState) -> bool {
+    if payload
+        .honeypot
+        .as_ref()
+        .is_some_and(|value| !value.trim().is_empty())
+    {
+        return true;
+    }
+
+    if state.comment_min_fill_seconds <= 0 {
+        return false;
+    }
+
+    let token = match payload.form_token.as_ref() {
+        Some(token) => token,
+        None => return state.comment_require_form_token,
+    };
+    let issued_at = state
+        .jwt_secrets()
+        .find_map(|secret| form_token::verify(token, secret));
+    let issued_at = match issued_at {
+        Some(issued_at) => issued_at,
+        None => return true,
+    };
+
+    Utc::now().timestamp() - issued_at < state.comment_min_fill_seconds
+}
+
+/// Canonical form of `url` if it's the website proven by the verified site
+/// cookie
+fn verified_site(state: &AppState, headers: &HeaderMap, url: &str) -> Option<String> {
+    let proof = CookieJar::from_headers(headers)
+        .get(VERIFIED_SITE_COOKIE_KEY)
+        .map(|cookie| cookie.value().to_string())?;
+    let me = state
+        .jwt_secrets()
+        .find_map(|secret| indieauth::verify_proof(&proof, secret))?;
+    let url = indieauth::canonicalize_profile_url(url).ok()?;
+
+    (url.as_str() == me).then_some(me)
 }
 
 pub async fn post(
-    AuthUserOrPublic { user }: AuthUserOrPublic,
+    auth: AuthUserOrPublic,
+    client: ClientInfo,
+    shadow_banned: Option<Extension<ShadowBanned>>,
+    headers: HeaderMap,
     State(state): State<AppState>,
     ValidatedJson(payload): ValidatedJson<AddCommentPayload>,
 ) -> impl IntoResponse {
-    let is_root = user.is_some() && user.unwrap().role == UserRole::Root;
+    // API tokens need the scope, and the post author status comes with it
+    if let Err(rejection) = auth.require_scope(TokenScope::CommentsWrite) {
+        return rejection.into_response();
+    }
+
+    let user = auth.user;
+    let is_anonymous = user.is_none();
+    let is_root = user
+        .as_ref()
+        .is_some_and(|user| user.role == UserRole::Root);
+
+    let own_name = user.as_ref().map(|user| user.name.as_str());
+
+    // Signed in users may keep using their own name, unless it's protected
+    if !is_root
+        && state
+            .user_names
+            .is_reserved(&payload.name, own_name, &state.protected_names)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "This name is reserved, please use another name" })),
+        )
+            .into_response();
+    }
+
+    let is_spam = !is_root && is_likely_spam(&payload, &state);
+    let verified_url = payload
+        .url
+        .as_deref()
+        .and_then(|url| verified_site(&state, &headers, url));
     let comment = Comment {
         id: None,
         post_slug: payload.post_slug,
         name: payload.name,
         email: payload.email.unwrap_or_default(),
-        url: payload.url.unwrap_or_default(),
+        verified_site: verified_url.is_some(),
+        url: verified_url.or(payload.url).unwrap_or_default(),
         body: payload.body,
         parent_comment_id: payload
             .parent_comment_id
             .and_then(|id| ObjectId::parse_str(&id).ok()),
         by_post_author: is_root,
+        author_id: user.and_then(|user| user.id),
+        tripcode: payload
+            .tripcode_secret
+            .filter(|_| is_anonymous)
+            .map(|secret| tripcode::derive(&secret, &state.tripcode_secret)),
         created_at: Utc::now(),
         updated_at: Utc::now(),
         replies: None,
+        shadow_banned: shadow_banned.is_some(),
+        moderation: Some(CommentModeration {
+            ip_hash: client.ip.map(|ip| hash_ip(&ip, &state.ip_hash_salt)),
+            user_agent: client.user_agent,
+        }),
+    };
+
+    if is_spam {
+        log::info!(
+            "Discarded likely spam comment by {} on {}",
+            comment.name,
+            comment.post_slug
+        );
+
+        // Pretend the comment was saved so bots don't adapt
+        let comment = Comment {
+            id: Some(ObjectId::new()),
+            ..comment
+        };
+
+        return (StatusCode::CREATED, Json(comment.to_response())).into_response();
+    }
+
+    let idempotency_key = headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|key| key.trim())
+        .filter(|key| !key.is_empty())
+        // Scope keys to the client so nobody can replay someone else's
+        // response, ignoring them when the client can't be told apart
+        .and_then(|key| idempotency_scope(&comment).map(|scope| format!("{}:{}", scope, key)));
+
+    let idempotency_key = match idempotency_key {
+        Some(key) => key,
+        None => {
+            let (status, body) = save(&state, comment).await;
+            return (status, Json(body)).into_response();
+        }
     };
+    let request_hash = hash_request(&comment);
 
-    let comment_create_result = match Comment::create(&state.db, comment.clone()).await {
+    match IdempotencyKey::claim(&state.db, &idempotency_key, &request_hash).await {
+        Ok(IdempotencyClaim::Acquired) => {}
+        Ok(IdempotencyClaim::Existing(existing)) if existing.request_hash != request_hash => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "message": "Idempotency-Key was already used for another request" })),
+            )
+                .into_response();
+        }
+        Ok(IdempotencyClaim::Existing(existing)) => {
+            return replay(existing);
+        }
+        Err(e) => {
+            log::error!("Failed to claim idempotency key: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to create comment" })),
+            )
+                .into_response();
+        }
+    }
+
+    let (status, body) = save(&state, comment).await;
+    let recorded = if status.is_success() {
+        IdempotencyKey::complete(
+            &state.db,
+            &idempotency_key,
+            status.as_u16(),
+            &body.to_string(),
+        )
+        .await
+    } else {
+        IdempotencyKey::release(&state.db, &idempotency_key).await
+    };
+
+    if let Err(e) = recorded {
+        log::error!("Failed to record idempotency key: {}", e);
+    }
+
+    (status, Json(body)).into_response()
+}
+
+/// Who an idempotency key belongs to: the author, or else the IP address
+/// or tripcode of an anonymous commenter
+fn idempotency_scope(comment: &Comment) -> Option<String> {
+    if let Some(author_id) = comment.author_id {
+        return Some(format!("user:{}", author_id.to_hex()));
+    }
+
+    if let Some(ip_hash) = comment
+        .moderation
+        .as_ref()
+        .and_then(|moderation| moderation.ip_hash.as_ref())
+    {
+        return Some(format!("ip:{}", ip_hash));
+    }
+
+    comment
+        .tripcode
+        .as_ref()
+        .map(|tripcode| format!("tripcode:{}", tripcode))
+}
+
+/// Respond with the response recorded for an idempotency key
+fn replay(existing: IdempotencyKey) -> Response {
+    let (status, body) = match (existing.status, existing.body) {
+        (Some(status), Some(body)) => (status, body),
+        _ => {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({ "message": "A request with this Idempotency-Key is in progress" })),
+            )
+                .into_response();
+        }
+    };
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+    let body = serde_json::from_str::<Value>(&body).unwrap_or_default();
+
+    (status, [(IDEMPOTENT_REPLAYED_HEADER, "true")], Json(body)).into_response()
+}
+
+fn hash_request(comment: &Comment) -> String {
+    let mut hasher = Sha256::new();
+
+    for field in [
+        comment.post_slug.as_str(),
+        comment.name.as_str(),
+        comment.email.as_str(),
+        comment.url.as_str(),
+        comment.body.as_str(),
+        &comment
+            .parent_comment_id
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+    ] {
+        hasher.update(field.as_bytes());
+        hasher.update([0]);
+    }
+
+    HEXLOWER.encode(&hasher.finalize())
+}
+
+/// Save the comment unless its author just posted the same one
+async fn save(state: &AppState, comment: Comment) -> (StatusCode, Value) {
+    let digest = comment.digest();
+    let expires_at = Utc::now() + Duration::seconds(state.comment_duplicate_window_seconds);
+
+    match CommentDigest::claim(&state.db, &digest, expires_at).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::CONFLICT,
+                json!({ "message": "You already posted the same comment" }),
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to check duplicate comment: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                json!({ "message": "Failed to create comment" }),
+            );
+        }
+    }
+
+    let comment_create_result = match Comment::create(&state.db, comment).await {
+        Ok(comment) if comment.shadow_banned => comment,
         Ok(comment) => {
             let comment_to_send = comment.clone();
 
@@ -93,13 +380,20 @@ pub async fn post(
         }
         Err(e) => {
             log::error!("Failed to create comment: {}", e);
+
+            if let Err(e) = CommentDigest::release(&state.db, &digest).await {
+                log::error!("Failed to release comment digest: {}", e);
+            }
+
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({ "message": "Failed to create comment" })),
-            )
-                .into_response();
+                json!({ "message": "Failed to create comment" }),
+            );
         }
     };
 
-    (StatusCode::CREATED, Json(json!(comment_create_result))).into_response()
+    (
+        StatusCode::CREATED,
+        json!(comment_create_result.to_response()),
+    )
 }
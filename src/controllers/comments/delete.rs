@@ -7,22 +7,18 @@ use axum::{
 use serde_json::json;
 
 use crate::{
-    auth::guard::AuthUser,
+    auth::{guard::AuthUser, permission::Permission},
     env::state::AppState,
-    models::{comment::Comment, user::UserRole},
+    models::comment::Comment,
 };
 
 pub async fn delete(
-    AuthUser { user }: AuthUser,
+    auth: AuthUser,
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
-    if user.role != UserRole::Root {
-        return (
-            StatusCode::FORBIDDEN,
-            Json(json!({ "message": "You don't have permission to delete this comment" })),
-        )
-            .into_response();
+    if let Err(rejection) = auth.require_permission(Permission::CommentDelete) {
+        return rejection.into_response();
     }
 
     match Comment::delete(&state.db, &id).await {
@@ -0,0 +1,45 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::comment::Comment,
+};
+
+pub async fn get(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::CommentRead) {
+        return rejection.into_response();
+    }
+
+    let comment = match Comment::find_by_id(&state.db, &id).await {
+        Ok(comment) => comment,
+        Err(e) => {
+            log::error!("Failed to get comment: {}", e);
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Comment not found" })),
+            )
+                .into_response();
+        }
+    };
+    let moderation = comment.moderation.unwrap_or_default();
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "ipHash": moderation.ip_hash,
+            "userAgent": moderation.user_agent,
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,14 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{env::state::AppState, utils::form_token};
+
+pub async fn get(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        Json(json!({
+            "token": form_token::issue(&state.jwt_secret),
+            "minFillSeconds": state.comment_min_fill_seconds,
+        })),
+    )
+}
@@ -7,7 +7,11 @@ use axum::{
 use serde::Deserialize;
 use serde_json::json;
 
-use crate::{env::state::AppState, models::comment::Comment};
+use crate::{
+    env::state::AppState,
+    models::comment::Comment,
+    utils::{client::ClientInfo, encryption::hash_ip},
+};
 
 #[derive(Deserialize)]
 pub struct ListCommentsQuery {
@@ -16,10 +20,12 @@ pub struct ListCommentsQuery {
 }
 
 pub async fn get(
+    client: ClientInfo,
     State(state): State<AppState>,
     Query(query): Query<ListCommentsQuery>,
 ) -> impl IntoResponse {
-    let comments = Comment::get_by_slug(&state.db, &query.slug).await;
+    let viewer_ip_hash = client.ip.map(|ip| hash_ip(&ip, &state.ip_hash_salt));
+    let comments = Comment::get_by_slug(&state.db, &query.slug, viewer_ip_hash.as_deref()).await;
 
     if comments.is_err() {
         log::error!("Failed to get comments: {:?}", comments.err());
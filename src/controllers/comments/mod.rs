@@ -1,3 +1,8 @@
+pub mod by_fingerprint;
 pub mod create;
 pub mod delete;
+pub mod fingerprint;
+pub mod form_token;
 pub mod list;
+pub mod tripcode;
+pub mod update;
@@ -0,0 +1,24 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{env::state::AppState, models::comment::Comment};
+
+pub async fn get(State(state): State<AppState>, Path(tripcode): Path<String>) -> impl IntoResponse {
+    let comments = Comment::get_by_tripcode(&state.db, &tripcode).await;
+
+    if comments.is_err() {
+        log::error!("Failed to get comments: {:?}", comments.err());
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to get comments" })),
+        )
+            .into_response();
+    }
+
+    (StatusCode::OK, Json(comments.unwrap())).into_response()
+}
@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{csrf, guard::AuthUserOrPublic, permission::Permission},
+    constants::auth::{LOGIN_MAX_FAILURES_PER_IP, LOGIN_MAX_FAILURES_PER_NAME},
+    controllers::auth::signin::{check_lockout, record_failures},
+    env::state::AppState,
+    models::{comment::Comment, login_attempt::LoginAttempt},
+    utils::{client::ClientInfo, encryption::hash_ip, tripcode, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct UpdateCommentPayload {
+    #[validate(length(min = 1, message = "Comment body cannot be empty"))]
+    pub body: String,
+
+    /// Secret of the anonymous commenter who wrote the comment
+    #[serde(rename = "tripcodeSecret")]
+    #[validate(length(min = 8, message = "Tripcode secret must be at least 8 characters"))]
+    pub tripcode_secret: Option<String>,
+}
+
+pub async fn patch(
+    auth: AuthUserOrPublic,
+    client: ClientInfo,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateCommentPayload>,
+) -> impl IntoResponse {
+    let comment = match Comment::find_by_id(&state.db, &id).await {
+        Ok(comment) => comment,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Comment not found" })),
+            )
+                .into_response();
+        }
+    };
+
+    let is_moderator = auth.has_permission(Permission::CommentEdit);
+    // API tokens of the author need the write scope
+    let is_author = match auth.is_author(comment.author_id.as_ref()) {
+        Ok(is_author) => is_author,
+        Err(_) if is_moderator => false,
+        Err(rejection) => return rejection.into_response(),
+    };
+    let mut is_tripcode_owner = false;
+
+    if !is_moderator && !is_author && comment.tripcode.is_some() {
+        if let Some(secret) = &payload.tripcode_secret {
+            // Guessed secrets are limited like passwords
+            let attempt_keys = attempt_keys(&state, &id, &client);
+
+            if let Some(response) = check_lockout(&state, &attempt_keys).await {
+                return response;
+            }
+
+            let derived = tripcode::derive(secret, &state.tripcode_secret);
+
+            is_tripcode_owner = comment
+                .tripcode
+                .as_deref()
+                .is_some_and(|tripcode| csrf::matches(tripcode, &derived));
+
+            if !is_tripcode_owner {
+                record_failures(&state, &attempt_keys).await;
+            }
+        }
+    }
+
+    if !is_moderator && !is_author && !is_tripcode_owner {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "You don't have permission to edit this comment" })),
+        )
+            .into_response();
+    }
+
+    match Comment::update_body(&state.db, &id, &payload.body).await {
+        Ok(comment) => (StatusCode::OK, Json(comment.to_response())).into_response(),
+        Err(e) => {
+            log::error!("Failed to update comment: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to update comment" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Keys failed tripcode secrets of the request are counted against, with
+/// their limit
+fn attempt_keys(state: &AppState, comment_id: &str, client: &ClientInfo) -> Vec<(String, i32)> {
+    // Clients without an address share one key per comment
+    let ip_hash = match &client.ip {
+        Some(ip) => hash_ip(ip, &state.ip_hash_salt),
+        None => {
+            return vec![(
+                LoginAttempt::tripcode_key(comment_id, "unknown"),
+                LOGIN_MAX_FAILURES_PER_NAME,
+            )]
+        }
+    };
+
+    vec![
+        (
+            LoginAttempt::tripcode_key(comment_id, &ip_hash),
+            LOGIN_MAX_FAILURES_PER_NAME,
+        ),
+        (
+            LoginAttempt::tripcode_ip_key(&ip_hash),
+            LOGIN_MAX_FAILURES_PER_IP,
+        ),
+    ]
+}
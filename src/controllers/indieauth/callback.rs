@@ -0,0 +1,201 @@
+use axum::{
+    extract::{Query, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::{
+        cookie::{csrf_cookie, indieauth_state_cookie, verified_site_cookie},
+        csrf, indieauth, session,
+    },
+    constants::auth::INDIEAUTH_STATE_COOKIE_KEY,
+    controllers::indieauth::start::{client_id, redirect_uri},
+    env::state::AppState,
+    models::indieauth_state::IndieAuthState,
+};
+
+#[derive(Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Issuer identifier of the authorization server
+    pub iss: Option<String>,
+    /// Set by the authorization server when the user denied the authorization
+    pub error: Option<String>,
+}
+
+/// Complete the verification of a commenter's website
+///
+/// Sets a cookie proving ownership of the website along with a CSRF token
+/// to send it with, then redirects to `OAUTH_SUCCESS_REDIRECT`.
+pub async fn get(
+    State(state): State<AppState>,
+    Query(query): Query<CallbackQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let mut response = complete(&state, query, &CookieJar::from_headers(&headers)).await;
+    let mut removal_cookie = indieauth_state_cookie(String::new(), state.cookie_domain.clone());
+
+    removal_cookie.make_removal();
+    response
+        .headers_mut()
+        .append(SET_COOKIE, removal_cookie.to_string().parse().unwrap());
+
+    response
+}
+
+/// Complete the verification, once the state matches the one of the state
+/// cookie
+async fn complete(state: &AppState, query: CallbackQuery, cookie_jar: &CookieJar) -> Response {
+    let state_cookie = cookie_jar
+        .get(INDIEAUTH_STATE_COOKIE_KEY)
+        .map(|cookie| cookie.value());
+
+    if let Some(error) = query.error {
+        log::info!("IndieAuth authorization failed: {}", error);
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "message": "Authorization was denied" })),
+        )
+            .into_response();
+    }
+
+    let (code, indieauth_state) = match (query.code, query.state) {
+        (Some(code), Some(indieauth_state)) => (code, indieauth_state),
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Missing code or state" })),
+            )
+                .into_response();
+        }
+    };
+
+    // A state issued to another browser means someone is trying to give the
+    // commenter a website that isn't theirs
+    if state_cookie != Some(indieauth_state.as_str()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Invalid or expired state" })),
+        )
+            .into_response();
+    }
+
+    let pending = match IndieAuthState::take(&state.db, &indieauth_state).await {
+        Ok(Some(pending)) => pending,
+        Ok(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid or expired state" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to get IndieAuth state: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to verify website" })),
+            )
+                .into_response();
+        }
+    };
+
+    if pending.issuer.is_some() && pending.issuer != query.iss {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "message": "Unexpected issuer" })),
+        )
+            .into_response();
+    }
+
+    let client = match indieauth::http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            log::error!("Failed to build IndieAuth client: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to verify website" })),
+            )
+                .into_response();
+        }
+    };
+    let server = pending.server();
+    let me = match indieauth::redeem(
+        &client,
+        &server,
+        &client_id(state),
+        &redirect_uri(state),
+        &code,
+        &pending.code_verifier,
+    )
+    .await
+    {
+        Ok(me) => me,
+        Err(e) => {
+            log::info!("Failed to redeem IndieAuth code for {}: {}", pending.me, e);
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Failed to verify the authorization" })),
+            )
+                .into_response();
+        }
+    };
+
+    // The server may return another profile URL, such as the one the entered
+    // URL redirects to, which must then be served by the same server
+    if me.as_str() != pending.me {
+        let is_same_server = indieauth::discover(&client, &me)
+            .await
+            .is_ok_and(|discovered| {
+                discovered.authorization_endpoint == server.authorization_endpoint
+            });
+
+        if !is_same_server {
+            log::info!("{} returned {} which it doesn't serve", pending.me, me);
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Failed to verify the authorization" })),
+            )
+                .into_response();
+        }
+    }
+
+    // The proof cookie is a credential, so comments sent with it need a CSRF
+    // token, bound to the session if the commenter is also signed in
+    let session_id = match session::from_cookies(state, cookie_jar).await {
+        Ok(session_id) => session_id,
+        Err(e) => {
+            log::error!("Failed to find session of CSRF token: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to verify website" })),
+            )
+                .into_response();
+        }
+    };
+    let proof = indieauth::issue_proof(me.as_str(), &state.jwt_secret);
+    let csrf_token = csrf::issue(&state.jwt_secret, session_id.as_deref());
+    let mut headers = HeaderMap::new();
+
+    headers.append(
+        SET_COOKIE,
+        verified_site_cookie(proof, state.cookie_domain.clone())
+            .to_string()
+            .parse()
+            .unwrap(),
+    );
+    headers.append(
+        SET_COOKIE,
+        csrf_cookie(csrf_token, state.cookie_domain.clone())
+            .to_string()
+            .parse()
+            .unwrap(),
+    );
+
+    (headers, Redirect::to(&state.oauth_success_redirect)).into_response()
+}
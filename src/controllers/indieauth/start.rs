@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Query, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::{cookie::indieauth_state_cookie, indieauth, oauth::code_challenge},
+    controllers::app::API_VERSION_PREFIX,
+    env::state::AppState,
+    models::indieauth_state::IndieAuthState,
+    utils::encryption::generate_token,
+};
+
+#[derive(Deserialize)]
+pub struct StartQuery {
+    /// Website of the commenter
+    pub me: String,
+}
+
+/// IndieAuth client ID, the root of the API host
+pub fn client_id(state: &AppState) -> String {
+    format!("{}/", state.oauth_redirect_base)
+}
+
+pub fn redirect_uri(state: &AppState) -> String {
+    format!(
+        "{}{}/auth/indieauth/callback",
+        state.oauth_redirect_base, API_VERSION_PREFIX
+    )
+}
+
+/// Send the commenter to the authorization server of their website
+pub async fn get(
+    State(state): State<AppState>,
+    Query(query): Query<StartQuery>,
+) -> impl IntoResponse {
+    let me = match indieauth::canonicalize_profile_url(&query.me) {
+        Ok(me) => me,
+        Err(message) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({ "message": message }))).into_response();
+        }
+    };
+
+    let client = match indieauth::http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            log::error!("Failed to build IndieAuth client: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start verification" })),
+            )
+                .into_response();
+        }
+    };
+
+    let server = match indieauth::discover(&client, &me).await {
+        Ok(server) => server,
+        Err(e) => {
+            log::info!("Failed to discover IndieAuth endpoints of {}: {}", me, e);
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "No IndieAuth authorization endpoint found on this website" })),
+            )
+                .into_response();
+        }
+    };
+
+    let code_verifier = generate_token();
+    let indieauth_state =
+        match IndieAuthState::create(&state.db, me.as_str(), &server, &code_verifier).await {
+            Ok(indieauth_state) => indieauth_state,
+            Err(e) => {
+                log::error!("Failed to create IndieAuth state: {}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "message": "Failed to start verification" })),
+                )
+                    .into_response();
+            }
+        };
+
+    match indieauth::authorization_url(
+        &server,
+        &client_id(&state),
+        &redirect_uri(&state),
+        &indieauth_state,
+        &code_challenge(&code_verifier),
+        me.as_str(),
+    ) {
+        Ok(url) => {
+            // The callback only accepts the state from the browser it was
+            // issued to
+            let mut headers = HeaderMap::new();
+
+            headers.append(
+                SET_COOKIE,
+                indieauth_state_cookie(indieauth_state, state.cookie_domain.clone())
+                    .to_string()
+                    .parse()
+                    .unwrap(),
+            );
+
+            (headers, Redirect::to(&url)).into_response()
+        }
+        Err(e) => {
+            log::info!("Invalid authorization endpoint of {}: {}", me, e);
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid authorization endpoint" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    constants::auth::INVITE_LIFETIME_IN_SECONDS,
+    env::state::AppState,
+    models::{invite::Invite, user::UserRole},
+    utils::validator::ValidatedJson,
+};
+
+#[derive(Deserialize, Validate)]
+pub struct CreateInvitePayload {
+    pub role: UserRole,
+
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub async fn post(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<CreateInvitePayload>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::InviteManage) {
+        return rejection.into_response();
+    }
+
+    let expires_at = payload
+        .expires_at
+        .unwrap_or_else(|| Utc::now() + Duration::seconds(INVITE_LIFETIME_IN_SECONDS));
+
+    if expires_at <= Utc::now() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Expiry must be in the future" })),
+        )
+            .into_response();
+    }
+
+    match Invite::create(&state.db, auth.user.id.unwrap(), payload.role, expires_at).await {
+        Ok((invite, code)) => (
+            StatusCode::CREATED,
+            Json(json!({
+                "code": code,
+                "invite": invite.to_response(),
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to create invite: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to create invite" })),
+            )
+                .into_response()
+        }
+    }
+}
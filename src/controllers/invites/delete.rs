@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::invite::Invite,
+};
+
+pub async fn delete(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::InviteManage) {
+        return rejection.into_response();
+    }
+
+    match Invite::revoke(&state.db, &id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(json!({ "message": "Invite revoked successfully" })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to revoke invite: {}", e);
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Invite not found" })),
+            )
+                .into_response()
+        }
+    }
+}
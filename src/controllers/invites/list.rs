@@ -0,0 +1,33 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::invite::{Invite, InviteResponse},
+};
+
+pub async fn get(auth: AuthUser, State(state): State<AppState>) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::InviteManage) {
+        return rejection.into_response();
+    }
+
+    let invites = Invite::find_all(&state.db).await;
+
+    if invites.is_err() {
+        log::error!("Failed to get invites: {:?}", invites.err());
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to get invites" })),
+        )
+            .into_response();
+    }
+
+    let invites = invites
+        .unwrap()
+        .iter()
+        .map(Invite::to_response)
+        .collect::<Vec<InviteResponse>>();
+
+    (StatusCode::OK, Json(invites)).into_response()
+}
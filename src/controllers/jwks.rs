@@ -0,0 +1,30 @@
+use axum::{
+    extract::State,
+    http::{header::CACHE_CONTROL, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::{json, Value};
+
+use crate::env::state::AppState;
+
+/// Public keys verifying access tokens, for other services
+///
+/// HMAC secrets are never listed, so the set is empty until a PEM key is
+/// configured.
+pub async fn get(State(state): State<AppState>) -> impl IntoResponse {
+    let keys = state
+        .token_config
+        .keyring
+        .keys()
+        .iter()
+        .filter(|key| state.token_config.algorithms.contains(&key.algorithm))
+        .filter_map(|key| key.to_jwk())
+        .collect::<Vec<Value>>();
+
+    (
+        StatusCode::OK,
+        [(CACHE_CONTROL, "public, max-age=3600")],
+        Json(json!({ "keys": keys })),
+    )
+}
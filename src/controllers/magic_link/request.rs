@@ -0,0 +1,105 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{magic_link, signup::SignupMode},
+    constants::auth::{MAGIC_LINK_MAX_REQUESTS_PER_EMAIL, MAGIC_LINK_MAX_REQUESTS_PER_IP},
+    controllers::{
+        app::API_VERSION_PREFIX,
+        auth::signin::{check_lockout, record_failures},
+    },
+    env::state::AppState,
+    models::{login_attempt::LoginAttempt, user::User},
+    utils::{client::ClientInfo, encryption::hash_ip, mailer, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct MagicLinkPayload {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
+/// Keys requests are counted against, with their limit
+fn request_keys(state: &AppState, email: &str, client: &ClientInfo) -> Vec<(String, i32)> {
+    let mut keys = vec![(
+        LoginAttempt::email_key(email),
+        MAGIC_LINK_MAX_REQUESTS_PER_EMAIL,
+    )];
+
+    if let Some(ip) = &client.ip {
+        keys.push((
+            LoginAttempt::magic_link_ip_key(&hash_ip(ip, &state.ip_hash_salt)),
+            MAGIC_LINK_MAX_REQUESTS_PER_IP,
+        ));
+    }
+
+    keys
+}
+
+/// Mail a link signing in as the owner of the address
+///
+/// The response is the same whether the address is registered or not, and
+/// the mail is sent in the background so the response time doesn't tell
+/// either.
+pub async fn post(
+    client: ClientInfo,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<MagicLinkPayload>,
+) -> impl IntoResponse {
+    if !mailer::is_configured() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "message": "Magic link sign in is not available" })),
+        )
+            .into_response();
+    }
+
+    let email = payload.email.trim().to_lowercase();
+    let request_keys = request_keys(&state, &email, &client);
+
+    if let Some(response) = check_lockout(&state, &request_keys).await {
+        return response;
+    }
+
+    record_failures(&state, &request_keys).await;
+    tokio::spawn(async move { send_link(&state, &email).await });
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({ "message": "If the address can sign in, a link was sent to it" })),
+    )
+        .into_response()
+}
+
+async fn send_link(state: &AppState, email: &str) {
+    let can_sign_in = match User::find_by_email(&state.db, email).await {
+        Ok(Some(_)) => true,
+        Ok(None) if state.signup_mode == SignupMode::Open => true,
+        Ok(None) => User::is_empty(&state.db).await.unwrap_or(false),
+        Err(e) => {
+            log::error!("Failed to get user by email: {}", e);
+            false
+        }
+    };
+
+    if !can_sign_in {
+        return;
+    }
+
+    let link = format!(
+        "{}{}/auth/magic-link/verify?token={}",
+        state.oauth_redirect_base,
+        API_VERSION_PREFIX,
+        magic_link::issue(email, &state.jwt_secret)
+    );
+    let body = format!(
+        "A sign in link was requested for {}.\n\nFollow it to sign in, it can be used once and expires in 15 minutes:\n\n{}\n\nIf you didn't request it, you can ignore this mail.\n",
+        email, link
+    );
+
+    if let Err(e) = mailer::send_mail(email, "Sign in link", body).await {
+        log::error!("Failed to send magic link mail: {}", e);
+    }
+}
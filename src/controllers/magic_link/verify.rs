@@ -0,0 +1,90 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::magic_link,
+    controllers::{auth::signup::create_passwordless, oauth::callback::redirect_signed_in},
+    env::state::AppState,
+    models::{login_attempt::LoginAttempt, revoked_token::RevokedToken, user::User},
+    utils::client::ClientInfo,
+};
+
+#[derive(Deserialize)]
+pub struct VerifyQuery {
+    pub token: String,
+}
+
+/// Sign in through a mailed link, creating the account on first use
+///
+/// Redirects to `OAUTH_SUCCESS_REDIRECT` the same way an OAuth sign in does.
+pub async fn get(
+    State(state): State<AppState>,
+    Query(query): Query<VerifyQuery>,
+    client: ClientInfo,
+) -> impl IntoResponse {
+    let link = state
+        .jwt_secrets()
+        .find_map(|secret| magic_link::verify(&query.token, secret));
+    let link = match link {
+        Some(link) => link,
+        None => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid or expired link" })),
+            )
+                .into_response();
+        }
+    };
+
+    match RevokedToken::revoke_once(&state.db, &link.jti, link.expires_at).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid or expired link" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to use magic link: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign in" })),
+            )
+                .into_response();
+        }
+    }
+
+    let user = match User::find_by_email(&state.db, &link.email).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            let name_hint = link.email.split('@').next().unwrap_or_default();
+
+            match create_passwordless(&state, name_hint, Some(&link.email)).await {
+                Ok(user) => user,
+                Err(response) => return response,
+            }
+        }
+        Err(e) => {
+            log::error!("Failed to get user by email: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign in" })),
+            )
+                .into_response();
+        }
+    };
+
+    // Following a link proves the address works, so its requests start over
+    if let Err(e) = LoginAttempt::clear(&state.db, &[LoginAttempt::email_key(&link.email)]).await {
+        log::error!("Failed to clear magic link requests: {}", e);
+    }
+
+    redirect_signed_in(&state, &user, &client).await
+}
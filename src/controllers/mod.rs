@@ -1,8 +1,20 @@
 mod __tests__;
 
+pub mod api_tokens;
 pub mod app;
+pub mod audit_log;
 pub mod auth;
+pub mod bans;
 pub mod comments;
 pub mod health;
+pub mod indieauth;
+pub mod invites;
+pub mod jwks;
+pub mod magic_link;
+pub mod oauth;
+pub mod passkeys;
 pub mod recent;
+pub mod sessions;
 pub mod thumbnail;
+pub mod totp;
+pub mod users;
@@ -0,0 +1,275 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::{challenge, cookie::oauth_state_cookie, session},
+    constants::auth::OAUTH_STATE_COOKIE_KEY,
+    controllers::{auth::signup::create_passwordless, oauth::start::redirect_uri},
+    env::state::AppState,
+    models::{identity::Identity, oauth_state::OAuthState, user::User},
+    utils::client::ClientInfo,
+};
+
+#[derive(Deserialize)]
+pub struct CallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set by the provider when the user denied the authorization
+    pub error: Option<String>,
+}
+
+/// Complete the sign in at the provider
+///
+/// Signs in the user linked to the identity, creating one on first use, then
+/// redirects to `OAUTH_SUCCESS_REDIRECT`. Users with TOTP enabled are sent
+/// there with a `challenge` parameter to finish at `/auth/signin/totp`.
+pub async fn get(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    Query(query): Query<CallbackQuery>,
+    client: ClientInfo,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let state_cookie = CookieJar::from_headers(&headers)
+        .get(OAUTH_STATE_COOKIE_KEY)
+        .map(|cookie| cookie.value().to_string());
+    let mut response = complete(&state, &provider, query, state_cookie, &client).await;
+    let mut removal_cookie = oauth_state_cookie(String::new(), state.cookie_domain.clone());
+
+    removal_cookie.make_removal();
+    response
+        .headers_mut()
+        .append(SET_COOKIE, removal_cookie.to_string().parse().unwrap());
+
+    response
+}
+
+/// Complete the sign in, once the state matches the one of `state_cookie`
+async fn complete(
+    state: &AppState,
+    provider: &str,
+    query: CallbackQuery,
+    state_cookie: Option<String>,
+    client: &ClientInfo,
+) -> Response {
+    let provider = match state.oauth_providers.iter().find(|p| p.name == provider) {
+        Some(provider) => provider,
+        None => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Unknown provider" })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(error) = query.error {
+        log::info!("Authorization at {} failed: {}", provider.name, error);
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "message": "Authorization was denied" })),
+        )
+            .into_response();
+    }
+
+    let (code, oauth_state) = match (query.code, query.state) {
+        (Some(code), Some(oauth_state)) => (code, oauth_state),
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Missing code or state" })),
+            )
+                .into_response();
+        }
+    };
+
+    // A state issued to another browser means someone is trying to sign the
+    // user in to their own account
+    if state_cookie.as_deref() != Some(oauth_state.as_str()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Invalid or expired state" })),
+        )
+            .into_response();
+    }
+
+    let pending = match OAuthState::take(&state.db, &provider.name, &oauth_state).await {
+        Ok(Some(pending)) => pending,
+        Ok(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid or expired state" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to get OAuth state: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign in" })),
+            )
+                .into_response();
+        }
+    };
+
+    let account = match provider
+        .exchange(
+            &redirect_uri(state, &provider.name),
+            &code,
+            &pending.code_verifier,
+            &pending.nonce,
+        )
+        .await
+    {
+        Ok(account) => account,
+        Err(e) => {
+            log::warn!("Failed to exchange code of {}: {}", provider.name, e);
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Failed to verify the authorization" })),
+            )
+                .into_response();
+        }
+    };
+
+    let identity = match Identity::find(&state.db, &provider.name, &account.subject).await {
+        Ok(identity) => identity,
+        Err(e) => {
+            log::error!("Failed to get identity: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign in" })),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(user_id) = pending.link_user_id {
+        return match identity {
+            Some(identity) if identity.user_id != user_id => (
+                StatusCode::CONFLICT,
+                Json(json!({ "message": "This account is linked to another user" })),
+            )
+                .into_response(),
+            Some(_) => Redirect::to(&state.oauth_success_redirect).into_response(),
+            None => {
+                match Identity::link(&state.db, user_id, &provider.name, &account.subject).await {
+                    Ok(_) => Redirect::to(&state.oauth_success_redirect).into_response(),
+                    Err(e) => {
+                        log::error!("Failed to link identity: {}", e);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({ "message": "Failed to link the account" })),
+                        )
+                            .into_response()
+                    }
+                }
+            }
+        };
+    }
+
+    let user = match identity {
+        Some(identity) => User::find_by_id(&state.db, &identity.user_id.to_hex())
+            .await
+            .map_err(|e| {
+                log::error!("Failed to get user of identity: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(json!({ "message": "Failed to sign in" })),
+                )
+                    .into_response()
+            }),
+        None => match create_passwordless(state, &account.name, None).await {
+            Ok(user) => {
+                match Identity::link(
+                    &state.db,
+                    user.id.unwrap(),
+                    &provider.name,
+                    &account.subject,
+                )
+                .await
+                {
+                    Ok(_) => Ok(user),
+                    Err(e) => {
+                        log::error!("Failed to link identity: {}", e);
+                        delete_orphan(state, &user).await;
+                        Err((
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            Json(json!({ "message": "Failed to create user" })),
+                        )
+                            .into_response())
+                    }
+                }
+            }
+            Err(response) => Err(response),
+        },
+    };
+
+    let user = match user {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    redirect_signed_in(state, &user, client).await
+}
+
+/// Redirect a user who signed in from another site to `OAUTH_SUCCESS_REDIRECT`
+///
+/// Users with TOTP enabled get a `challenge` parameter to finish signing in
+/// at `/auth/signin/totp`, the others get the session cookies.
+pub async fn redirect_signed_in(state: &AppState, user: &User, client: &ClientInfo) -> Response {
+    if user.is_disabled() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "This account is disabled" })),
+        )
+            .into_response();
+    }
+
+    if user.is_totp_enabled() {
+        let challenge = challenge::issue(&user.id.unwrap().to_hex(), &state.jwt_secret);
+
+        // The challenge is made of URL-safe characters
+        let separator = if state.oauth_success_redirect.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+
+        return Redirect::to(&format!(
+            "{}{}challenge={}",
+            state.oauth_success_redirect, separator, challenge
+        ))
+        .into_response();
+    }
+
+    match session::issue(state, user, client).await {
+        Ok(headers) => (headers, Redirect::to(&state.oauth_success_redirect)).into_response(),
+        Err(e) => {
+            log::error!("Failed to issue tokens: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to generate token" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Delete a user created for an identity that couldn't be linked, so the
+/// next sign in doesn't create another one
+async fn delete_orphan(state: &AppState, user: &User) {
+    if let Err(e) = User::delete(&state.db, user.id.unwrap()).await {
+        log::error!("Failed to delete unlinked user: {}", e);
+        return;
+    }
+
+    state.user_names.remove(&user.name);
+}
@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{cookie::oauth_state_cookie, guard::AuthUserOrPublic},
+    controllers::app::API_VERSION_PREFIX,
+    env::state::AppState,
+    models::oauth_state::OAuthState,
+    utils::encryption::generate_token,
+};
+
+/// Callback URL registered at the provider
+pub fn redirect_uri(state: &AppState, provider: &str) -> String {
+    format!(
+        "{}{}/auth/oauth/{}/callback",
+        state.oauth_redirect_base, API_VERSION_PREFIX, provider
+    )
+}
+
+/// Send the user to the provider to sign in
+///
+/// When the request comes with a session, the identity is linked to the
+/// signed in user instead.
+pub async fn get(
+    AuthUserOrPublic { user, scopes }: AuthUserOrPublic,
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> impl IntoResponse {
+    let provider = match state.oauth_providers.iter().find(|p| p.name == provider) {
+        Some(provider) => provider,
+        None => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Unknown provider" })),
+            )
+                .into_response();
+        }
+    };
+
+    // API tokens can't link identities
+    let link_user_id = user.filter(|_| scopes.is_none()).and_then(|user| user.id);
+    let code_verifier = generate_token();
+    let nonce = generate_token();
+
+    let oauth_state = match OAuthState::create(
+        &state.db,
+        &provider.name,
+        &code_verifier,
+        &nonce,
+        link_user_id,
+    )
+    .await
+    {
+        Ok(oauth_state) => oauth_state,
+        Err(e) => {
+            log::error!("Failed to create OAuth state: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start sign in" })),
+            )
+                .into_response();
+        }
+    };
+
+    match provider
+        .authorization_url(
+            &redirect_uri(&state, &provider.name),
+            &oauth_state,
+            &code_verifier,
+            &nonce,
+        )
+        .await
+    {
+        Ok(url) => {
+            // The callback only accepts the state from the browser it was
+            // issued to
+            let mut headers = HeaderMap::new();
+
+            headers.append(
+                SET_COOKIE,
+                oauth_state_cookie(oauth_state, state.cookie_domain.clone())
+                    .to_string()
+                    .parse()
+                    .unwrap(),
+            );
+
+            (headers, Redirect::to(&url)).into_response()
+        }
+        Err(e) => {
+            log::error!(
+                "Failed to get authorization URL of {}: {}",
+                provider.name,
+                e
+            );
+            (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({ "message": "Failed to reach the provider" })),
+            )
+                .into_response()
+        }
+    }
+}
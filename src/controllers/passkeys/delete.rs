@@ -0,0 +1,38 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::guard::AuthUser, env::state::AppState, models::passkey_credential::PasskeyCredential,
+};
+
+pub async fn delete(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage passkeys" })),
+        );
+    }
+
+    match PasskeyCredential::delete(&state.db, user.id.unwrap(), &id).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(json!({ "message": "Passkey deleted successfully" })),
+        ),
+        Err(e) => {
+            log::error!("Failed to delete passkey: {}", e);
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Passkey not found" })),
+            )
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{
+    auth::guard::AuthUser,
+    env::state::AppState,
+    models::passkey_credential::{PasskeyCredential, PasskeyCredentialResponse},
+};
+
+pub async fn get(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage passkeys" })),
+        )
+            .into_response();
+    }
+
+    let passkey_credentials = PasskeyCredential::find_by_user(&state.db, user.id.unwrap()).await;
+
+    if passkey_credentials.is_err() {
+        log::error!("Failed to get passkeys: {:?}", passkey_credentials.err());
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to get passkeys" })),
+        )
+            .into_response();
+    }
+
+    let passkey_credentials = passkey_credentials
+        .unwrap()
+        .iter()
+        .map(PasskeyCredential::to_response)
+        .collect::<Vec<PasskeyCredentialResponse>>();
+
+    (StatusCode::OK, Json(passkey_credentials)).into_response()
+}
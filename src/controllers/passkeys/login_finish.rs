@@ -0,0 +1,126 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+use webauthn_rs::prelude::{PasskeyAuthentication, PublicKeyCredential};
+
+use crate::{
+    auth::passkey::encode_credential_id,
+    controllers::auth::signin::{attempt_keys, check_lockout, complete, record_failures},
+    env::state::AppState,
+    models::{
+        passkey_credential::PasskeyCredential,
+        user::User,
+        webauthn_challenge::{CeremonyKind, WebauthnChallenge},
+    },
+    utils::{client::ClientInfo, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct FinishLoginPayload {
+    #[serde(rename = "challengeId")]
+    pub challenge_id: String,
+
+    /// Response of `navigator.credentials.get()`
+    pub credential: PublicKeyCredential,
+}
+
+/// Sign in with a passkey, issuing the same cookies as a password signin
+pub async fn post(
+    client: ClientInfo,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<FinishLoginPayload>,
+) -> impl IntoResponse {
+    let (user_id, authentication) = match WebauthnChallenge::take::<PasskeyAuthentication>(
+        &state.db,
+        &payload.challenge_id,
+        CeremonyKind::Authentication,
+    )
+    .await
+    {
+        Ok(Some(challenge)) => challenge,
+        Ok(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid or expired challenge" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to get passkey authentication: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign in" })),
+            )
+                .into_response();
+        }
+    };
+
+    let user = match User::find_by_id(&state.db, &user_id.to_hex()).await {
+        Ok(user) => user,
+        Err(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid or expired challenge" })),
+            )
+                .into_response();
+        }
+    };
+    let attempt_keys = attempt_keys(&state, &user.name, &client);
+
+    if let Some(response) = check_lockout(&state, &attempt_keys).await {
+        return response;
+    }
+
+    let result = match state
+        .webauthn
+        .finish_passkey_authentication(&payload.credential, &authentication)
+    {
+        Ok(result) => result,
+        Err(e) => {
+            log::info!("Rejected passkey authentication: {}", e);
+            record_failures(&state, &attempt_keys).await;
+
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid passkey" })),
+            )
+                .into_response();
+        }
+    };
+
+    let credential_id = encode_credential_id(result.cred_id().as_ref());
+
+    match PasskeyCredential::find_by_credential_id(&state.db, &credential_id).await {
+        Ok(Some(mut passkey_credential)) if passkey_credential.user_id == user_id => {
+            passkey_credential.passkey.update_credential(&result);
+
+            if let Err(e) = PasskeyCredential::record_use(
+                &state.db,
+                passkey_credential.id.unwrap(),
+                &passkey_credential.passkey,
+            )
+            .await
+            {
+                log::error!("Failed to update passkey: {}", e);
+            }
+        }
+        Ok(_) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid passkey" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to get passkey: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign in" })),
+            )
+                .into_response();
+        }
+    }
+
+    complete(&state, &user, &client, &attempt_keys).await
+}
@@ -0,0 +1,83 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+use webauthn_rs::prelude::Passkey;
+
+use crate::{
+    env::state::AppState,
+    models::{
+        passkey_credential::PasskeyCredential,
+        user::User,
+        webauthn_challenge::{CeremonyKind, WebauthnChallenge},
+    },
+    utils::validator::ValidatedJson,
+};
+
+#[derive(Deserialize, Validate)]
+pub struct StartLoginPayload {
+    #[validate(length(min = 1, message = "Name cannot be empty"))]
+    pub name: String,
+}
+
+/// Start signing in with one of the passkeys of a user
+pub async fn post(
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<StartLoginPayload>,
+) -> impl IntoResponse {
+    let user_id = User::find_by_name(&state.db, &payload.name)
+        .await
+        .ok()
+        .and_then(|user| user.id);
+    let passkeys = match user_id {
+        Some(user_id) => PasskeyCredential::find_by_user(&state.db, user_id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|passkey_credential| passkey_credential.passkey)
+            .collect::<Vec<Passkey>>(),
+        None => Vec::new(),
+    };
+
+    if passkeys.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "No passkey is registered for this name" })),
+        );
+    }
+
+    let (options, authentication) = match state.webauthn.start_passkey_authentication(&passkeys) {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!("Failed to start passkey authentication: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start passkey sign in" })),
+            );
+        }
+    };
+
+    match WebauthnChallenge::create(
+        &state.db,
+        user_id.unwrap(),
+        CeremonyKind::Authentication,
+        &authentication,
+    )
+    .await
+    {
+        Ok(challenge_id) => (
+            StatusCode::OK,
+            Json(json!({
+                "challengeId": challenge_id.to_hex(),
+                "options": options,
+            })),
+        ),
+        Err(e) => {
+            log::error!("Failed to store passkey authentication: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start passkey sign in" })),
+            )
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod delete;
+pub mod list;
+pub mod login_finish;
+pub mod login_start;
+pub mod register_finish;
+pub mod register_start;
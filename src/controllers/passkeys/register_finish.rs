@@ -0,0 +1,113 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+use webauthn_rs::prelude::{PasskeyRegistration, RegisterPublicKeyCredential};
+
+use crate::{
+    auth::{guard::AuthUser, passkey::encode_credential_id},
+    database::is_duplicate_key_error,
+    env::state::AppState,
+    models::{
+        passkey_credential::PasskeyCredential,
+        webauthn_challenge::{CeremonyKind, WebauthnChallenge},
+    },
+    utils::validator::ValidatedJson,
+};
+
+#[derive(Deserialize, Validate)]
+pub struct FinishRegistrationPayload {
+    #[serde(rename = "challengeId")]
+    pub challenge_id: String,
+
+    #[validate(length(min = 1, max = 64, message = "Name must be 1 to 64 characters"))]
+    pub name: String,
+
+    /// Response of `navigator.credentials.create()`
+    pub credential: RegisterPublicKeyCredential,
+}
+
+pub async fn post(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<FinishRegistrationPayload>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage passkeys" })),
+        )
+            .into_response();
+    }
+
+    let user_id = user.id.unwrap();
+    let registration = match WebauthnChallenge::take::<PasskeyRegistration>(
+        &state.db,
+        &payload.challenge_id,
+        CeremonyKind::Registration,
+    )
+    .await
+    {
+        Ok(Some((challenge_user_id, registration))) if challenge_user_id == user_id => registration,
+        Ok(_) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid or expired challenge" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to get passkey registration: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to register passkey" })),
+            )
+                .into_response();
+        }
+    };
+
+    let passkey = match state
+        .webauthn
+        .finish_passkey_registration(&payload.credential, &registration)
+    {
+        Ok(passkey) => passkey,
+        Err(e) => {
+            log::info!("Rejected passkey registration: {}", e);
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid passkey" })),
+            )
+                .into_response();
+        }
+    };
+
+    let passkey_credential = PasskeyCredential {
+        id: None,
+        user_id,
+        name: payload.name,
+        credential_id: encode_credential_id(passkey.cred_id().as_ref()),
+        passkey,
+        last_used_at: None,
+        created_at: Utc::now(),
+    };
+
+    match PasskeyCredential::create(&state.db, passkey_credential).await {
+        Ok(passkey_credential) => {
+            (StatusCode::CREATED, Json(passkey_credential.to_response())).into_response()
+        }
+        Err(e) if is_duplicate_key_error(&e) => (
+            StatusCode::CONFLICT,
+            Json(json!({ "message": "This passkey is already registered" })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to save passkey: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to register passkey" })),
+            )
+                .into_response()
+        }
+    }
+}
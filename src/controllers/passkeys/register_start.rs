@@ -0,0 +1,81 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, passkey::user_handle},
+    env::state::AppState,
+    models::{
+        passkey_credential::PasskeyCredential,
+        webauthn_challenge::{CeremonyKind, WebauthnChallenge},
+    },
+};
+
+/// Start registering a passkey for the signed in user
+pub async fn post(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage passkeys" })),
+        );
+    }
+
+    let user_id = user.id.unwrap();
+    let existing = match PasskeyCredential::find_by_user(&state.db, user_id).await {
+        Ok(existing) => existing,
+        Err(e) => {
+            log::error!("Failed to get passkeys: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start passkey registration" })),
+            );
+        }
+    };
+    // Don't register the same authenticator twice
+    let exclude_credentials = existing
+        .iter()
+        .map(|existing| existing.passkey.cred_id().clone())
+        .collect();
+
+    let (options, registration) = match state.webauthn.start_passkey_registration(
+        user_handle(user_id),
+        &user.name,
+        &user.name,
+        Some(exclude_credentials),
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!("Failed to start passkey registration: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start passkey registration" })),
+            );
+        }
+    };
+
+    match WebauthnChallenge::create(
+        &state.db,
+        user_id,
+        CeremonyKind::Registration,
+        &registration,
+    )
+    .await
+    {
+        Ok(challenge_id) => (
+            StatusCode::OK,
+            Json(json!({
+                "challengeId": challenge_id.to_hex(),
+                "options": options,
+            })),
+        ),
+        Err(e) => {
+            log::error!("Failed to store passkey registration: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start passkey registration" })),
+            )
+        }
+    }
+}
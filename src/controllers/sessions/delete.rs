@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Path, State},
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use bson::oid::ObjectId;
+use serde_json::json;
+
+use crate::{
+    auth::{cookie::removal_cookies, guard::AuthUser},
+    env::state::AppState,
+    models::{refresh_token::RefreshToken, session::Session},
+};
+
+/// Sign out a session of the current user
+///
+/// Revoking the current session also removes the token cookies.
+pub async fn delete(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if auth.scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage sessions" })),
+        )
+            .into_response();
+    }
+
+    let session_id = match ObjectId::parse_str(&id) {
+        Ok(session_id) => session_id,
+        Err(_) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Session not found" })),
+            )
+                .into_response();
+        }
+    };
+
+    match Session::revoke(&state.db, auth.user.id.unwrap(), session_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "message": "Session not found" })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            log::error!("Failed to revoke session: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to revoke session" })),
+            )
+                .into_response();
+        }
+    }
+
+    if let Err(e) = RefreshToken::revoke_family(&state.db, session_id).await {
+        log::error!("Failed to revoke refresh tokens of session: {}", e);
+    }
+
+    let mut headers = HeaderMap::new();
+
+    if auth.session_id == Some(session_id) {
+        for cookie in removal_cookies(state.cookie_domain) {
+            headers.append(SET_COOKIE, cookie.to_string().parse().unwrap());
+        }
+    }
+
+    (
+        StatusCode::OK,
+        headers,
+        Json(json!({ "message": "Session revoked successfully" })),
+    )
+        .into_response()
+}
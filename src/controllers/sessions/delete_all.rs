@@ -0,0 +1,52 @@
+use axum::{
+    extract::State,
+    http::{header::SET_COOKIE, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{cookie::removal_cookies, guard::AuthUser},
+    env::state::AppState,
+    models::{refresh_token::RefreshToken, session::Session},
+};
+
+/// Sign out every session of the current user, including this one
+pub async fn delete(auth: AuthUser, State(state): State<AppState>) -> impl IntoResponse {
+    if auth.scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage sessions" })),
+        )
+            .into_response();
+    }
+
+    let user_id = auth.user.id.unwrap();
+
+    if let Err(e) = Session::revoke_user(&state.db, user_id).await {
+        log::error!("Failed to revoke sessions: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to revoke sessions" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = RefreshToken::revoke_user(&state.db, user_id).await {
+        log::error!("Failed to revoke refresh tokens: {}", e);
+    }
+
+    let mut headers = HeaderMap::new();
+
+    for cookie in removal_cookies(state.cookie_domain) {
+        headers.append(SET_COOKIE, cookie.to_string().parse().unwrap());
+    }
+
+    (
+        StatusCode::OK,
+        headers,
+        Json(json!({ "message": "Sessions revoked successfully" })),
+    )
+        .into_response()
+}
@@ -0,0 +1,38 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{
+    auth::guard::AuthUser,
+    env::state::AppState,
+    models::session::{Session, SessionResponse},
+};
+
+/// Signed in sessions of the current user, the most recently active first
+pub async fn get(auth: AuthUser, State(state): State<AppState>) -> impl IntoResponse {
+    if auth.scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage sessions" })),
+        )
+            .into_response();
+    }
+
+    let sessions = Session::find_active_by_user(&state.db, auth.user.id.unwrap()).await;
+
+    if sessions.is_err() {
+        log::error!("Failed to get sessions: {:?}", sessions.err());
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to get sessions" })),
+        )
+            .into_response();
+    }
+
+    let sessions = sessions
+        .unwrap()
+        .iter()
+        .map(|session| session.to_response(auth.session_id))
+        .collect::<Vec<SessionResponse>>();
+
+    (StatusCode::OK, Json(sessions)).into_response()
+}
@@ -0,0 +1,3 @@
+pub mod delete;
+pub mod delete_all;
+pub mod list;
@@ -0,0 +1,79 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::guard::AuthUser,
+    env::state::AppState,
+    models::user::User,
+    utils::{encryption::hash_token, totp, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct ConfirmTotpPayload {
+    #[validate(length(min = 1, message = "Code cannot be empty"))]
+    pub code: String,
+}
+
+/// Enable TOTP with a first code from the authenticator, returning the
+/// recovery codes
+pub async fn post(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<ConfirmTotpPayload>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage two-factor authentication" })),
+        );
+    }
+
+    let user_totp = match &user.totp {
+        Some(user_totp) if user_totp.enabled_at.is_none() => user_totp,
+        Some(_) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({ "message": "Two-factor authentication is already enabled" })),
+            );
+        }
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Start enrollment first" })),
+            );
+        }
+    };
+
+    let step = match totp::verify(&user_totp.secret, &payload.code, Utc::now().timestamp(), 0) {
+        Some(step) => step,
+        None => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "message": "Invalid code" })),
+            );
+        }
+    };
+
+    let recovery_codes = totp::generate_recovery_codes();
+    let recovery_code_hashes = recovery_codes
+        .iter()
+        .map(|code| hash_token(&totp::normalize_recovery_code(code)))
+        .collect();
+
+    match User::enable_totp(&state.db, user.id.unwrap(), step, recovery_code_hashes).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(json!({ "recoveryCodes": recovery_codes })),
+        ),
+        Err(e) => {
+            log::error!("Failed to enable TOTP: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to enable two-factor authentication" })),
+            )
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{guard::AuthUser, second_factor},
+    env::state::AppState,
+    models::user::User,
+    utils::{encryption::verify_password, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct DisableTotpPayload {
+    #[validate(length(min = 1, message = "Password cannot be empty"))]
+    pub password: String,
+
+    /// TOTP code or recovery code
+    #[validate(length(min = 1, message = "Code cannot be empty"))]
+    pub code: String,
+}
+
+pub async fn post(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<DisableTotpPayload>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage two-factor authentication" })),
+        );
+    }
+
+    if !user.is_totp_enabled() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "Two-factor authentication is not enabled" })),
+        );
+    }
+
+    if !verify_password(&payload.password, &user.password).unwrap_or(false) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "message": "Password is incorrect" })),
+        );
+    }
+
+    match second_factor::verify(&state.db, &user, &payload.code).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid code" })),
+            );
+        }
+        Err(e) => {
+            log::error!("Failed to verify TOTP code: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to disable two-factor authentication" })),
+            );
+        }
+    }
+
+    match User::disable_totp(&state.db, user.id.unwrap()).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(json!({ "message": "Two-factor authentication disabled" })),
+        ),
+        Err(e) => {
+            log::error!("Failed to disable TOTP: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to disable two-factor authentication" })),
+            )
+        }
+    }
+}
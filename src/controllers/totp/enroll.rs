@@ -0,0 +1,43 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde_json::json;
+
+use crate::{
+    auth::guard::AuthUser, constants::auth::AUTH_REALM, env::state::AppState, models::user::User,
+    utils::totp,
+};
+
+/// Start TOTP enrollment, returning the secret to add to an authenticator
+pub async fn post(
+    AuthUser { user, scopes, .. }: AuthUser,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    if scopes.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "message": "API tokens can't manage two-factor authentication" })),
+        );
+    }
+
+    let secret = totp::generate_secret();
+
+    match User::start_totp(&state.db, user.id.unwrap(), &secret).await {
+        Ok(true) => (
+            StatusCode::CREATED,
+            Json(json!({
+                "secret": secret,
+                "uri": totp::provisioning_uri(AUTH_REALM, &user.name, &secret),
+            })),
+        ),
+        Ok(false) => (
+            StatusCode::CONFLICT,
+            Json(json!({ "message": "Two-factor authentication is already enabled" })),
+        ),
+        Err(e) => {
+            log::error!("Failed to start TOTP enrollment: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to start enrollment" })),
+            )
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod confirm;
+pub mod disable;
+pub mod enroll;
+pub mod signin;
@@ -0,0 +1,76 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::{
+    auth::{challenge, second_factor},
+    controllers::auth::signin::{attempt_keys, check_lockout, complete, record_failures},
+    env::state::AppState,
+    models::user::User,
+    utils::{client::ClientInfo, validator::ValidatedJson},
+};
+
+#[derive(Deserialize, Validate)]
+pub struct TotpSignInPayload {
+    /// Challenge returned by the password step
+    #[validate(length(min = 1, message = "Challenge cannot be empty"))]
+    pub challenge: String,
+
+    /// TOTP code or recovery code
+    #[validate(length(min = 1, message = "Code cannot be empty"))]
+    pub code: String,
+}
+
+/// Second step of the signin of users with TOTP enabled
+pub async fn post(
+    client: ClientInfo,
+    State(state): State<AppState>,
+    ValidatedJson(payload): ValidatedJson<TotpSignInPayload>,
+) -> impl IntoResponse {
+    let user_id = state
+        .jwt_secrets()
+        .find_map(|secret| challenge::verify(&payload.challenge, secret));
+    let user = match user_id {
+        Some(user_id) => User::find_by_id(&state.db, &user_id).await.ok(),
+        None => None,
+    };
+
+    let user = match user.filter(|user| user.is_totp_enabled()) {
+        Some(user) => user,
+        None => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid or expired challenge" })),
+            )
+                .into_response();
+        }
+    };
+
+    let attempt_keys = attempt_keys(&state, &user.name, &client);
+
+    if let Some(response) = check_lockout(&state, &attempt_keys).await {
+        return response;
+    }
+
+    match second_factor::verify(&state.db, &user, &payload.code).await {
+        Ok(true) => complete(&state, &user, &client, &attempt_keys).await,
+        Ok(false) => {
+            record_failures(&state, &attempt_keys).await;
+
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "message": "Invalid code" })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            log::error!("Failed to verify TOTP code: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to sign in" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    controllers::users::get::find_target,
+    env::state::AppState,
+    models::comment::{Comment, CommentResponse},
+};
+
+/// Comments written by the user, including shadow banned ones
+pub async fn get(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let user = match find_target(&state, &id).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    match Comment::find_by_author(&state.db, user.id.unwrap()).await {
+        Ok(comments) => (
+            StatusCode::OK,
+            Json(
+                comments
+                    .iter()
+                    .map(Comment::to_response)
+                    .collect::<Vec<CommentResponse>>(),
+            ),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to get comments of user: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to get comments" })),
+            )
+                .into_response()
+        }
+    }
+}
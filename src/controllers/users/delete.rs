@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bson::doc;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    controllers::users::get::find_target,
+    env::state::AppState,
+    models::{
+        api_token::ApiToken,
+        audit_log::{AuditAction, AuditLog},
+        identity::Identity,
+        passkey_credential::PasskeyCredential,
+        refresh_token::RefreshToken,
+        session::Session,
+        user::User,
+    },
+};
+
+/// Delete the account and its credentials
+///
+/// Comments of the user are kept, with their author name.
+pub async fn delete(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let user = match find_target(&state, &id).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+    let user_id = user.id.unwrap();
+
+    if user.id == auth.user.id {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "You can't delete your own account" })),
+        )
+            .into_response();
+    }
+
+    // Credentials go first, so a failure leaves an account that can't be
+    // used rather than credentials of a deleted account
+    let revocations = [
+        RefreshToken::revoke_user(&state.db, user_id).await,
+        Session::revoke_user(&state.db, user_id).await,
+        ApiToken::delete_by_user(&state.db, user_id).await,
+        PasskeyCredential::delete_by_user(&state.db, user_id).await,
+        Identity::delete_by_user(&state.db, user_id).await,
+    ];
+
+    for result in revocations {
+        if let Err(e) = result {
+            log::error!("Failed to revoke credentials of user: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to delete user" })),
+            )
+                .into_response();
+        }
+    }
+
+    if let Err(e) = User::delete(&state.db, user_id).await {
+        log::error!("Failed to delete user: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to delete user" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = AuditLog::record(
+        &state.db,
+        auth.user.id.unwrap(),
+        AuditAction::UserDelete,
+        user_id,
+        doc! {"name": &user.name, "role": user.role.to_string()},
+    )
+    .await
+    {
+        log::error!("Failed to record user deletion: {}", e);
+    }
+
+    (StatusCode::OK, Json(json!({ "message": "User deleted" }))).into_response()
+}
@@ -0,0 +1,77 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bson::doc;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    controllers::users::get::find_target,
+    env::state::AppState,
+    models::{
+        audit_log::{AuditAction, AuditLog},
+        refresh_token::RefreshToken,
+        session::Session,
+        user::User,
+    },
+};
+
+/// Disable the account, signing the user out everywhere
+pub async fn post(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let user = match find_target(&state, &id).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    if user.id == auth.user.id {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "You can't disable your own account" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = User::set_disabled(&state.db, user.id.unwrap(), true).await {
+        log::error!("Failed to disable user: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to disable user" })),
+        )
+            .into_response();
+    }
+
+    // Access tokens are rejected by the guard, sessions are revoked so
+    // re-enabling the account doesn't bring them back
+    if let Err(e) = RefreshToken::revoke_user(&state.db, user.id.unwrap()).await {
+        log::error!("Failed to revoke refresh tokens: {}", e);
+    }
+
+    if let Err(e) = Session::revoke_user(&state.db, user.id.unwrap()).await {
+        log::error!("Failed to revoke sessions: {}", e);
+    }
+
+    if let Err(e) = AuditLog::record(
+        &state.db,
+        auth.user.id.unwrap(),
+        AuditAction::UserDisable,
+        user.id.unwrap(),
+        doc! {"name": &user.name},
+    )
+    .await
+    {
+        log::error!("Failed to record user disabling: {}", e);
+    }
+
+    (StatusCode::OK, Json(json!({ "message": "User disabled" }))).into_response()
+}
@@ -0,0 +1,56 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bson::doc;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    controllers::users::get::find_target,
+    env::state::AppState,
+    models::{
+        audit_log::{AuditAction, AuditLog},
+        user::User,
+    },
+};
+
+pub async fn post(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let user = match find_target(&state, &id).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    if let Err(e) = User::set_disabled(&state.db, user.id.unwrap(), false).await {
+        log::error!("Failed to enable user: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to enable user" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = AuditLog::record(
+        &state.db,
+        auth.user.id.unwrap(),
+        AuditAction::UserEnable,
+        user.id.unwrap(),
+        doc! {"name": &user.name},
+    )
+    .await
+    {
+        log::error!("Failed to record user enabling: {}", e);
+    }
+
+    (StatusCode::OK, Json(json!({ "message": "User enabled" }))).into_response()
+}
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use bson::oid::ObjectId;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::user::User,
+};
+
+/// Find the user an administration endpoint acts on
+pub async fn find_target(state: &AppState, id: &str) -> Result<User, Response> {
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "message": "User not found" })),
+        )
+            .into_response()
+    };
+    let id = ObjectId::parse_str(id).map_err(|_| not_found())?;
+
+    match User::find_by_object_id(&state.db, id).await {
+        Ok(Some(user)) => Ok(user),
+        Ok(None) => Err(not_found()),
+        Err(e) => {
+            log::error!("Failed to get user: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to get user" })),
+            )
+                .into_response())
+        }
+    }
+}
+
+pub async fn get(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    match find_target(&state, &id).await {
+        Ok(user) => (StatusCode::OK, Json(user.to_response())).into_response(),
+        Err(response) => response,
+    }
+}
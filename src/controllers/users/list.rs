@@ -0,0 +1,76 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    env::state::AppState,
+    models::user::{User, UserResponse},
+};
+
+const MAX_LIMIT: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct UsersQuery {
+    /// Page to get, starting from 1
+    #[serde(default = "default_page")]
+    pub page: u64,
+
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+
+    /// Part of the name or email of the users to get
+    pub search: Option<String>,
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_limit() -> i64 {
+    20
+}
+
+pub async fn get(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<UsersQuery>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let page = query.page.max(1);
+    let limit = query.limit.clamp(1, MAX_LIMIT);
+    let search = query
+        .search
+        .as_deref()
+        .map(str::trim)
+        .filter(|search| !search.is_empty());
+
+    match User::find_page(&state.db, search, (page - 1) * limit as u64, limit).await {
+        Ok((users, total)) => (
+            StatusCode::OK,
+            Json(json!({
+                "users": users.iter().map(User::to_response).collect::<Vec<UserResponse>>(),
+                "total": total,
+                "page": page,
+                "limit": limit,
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to get users: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to get users" })),
+            )
+                .into_response()
+        }
+    }
+}
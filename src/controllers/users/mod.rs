@@ -0,0 +1,8 @@
+pub mod comments;
+pub mod delete;
+pub mod disable;
+pub mod enable;
+pub mod get;
+pub mod list;
+pub mod sessions;
+pub mod update_role;
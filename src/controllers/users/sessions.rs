@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    controllers::users::get::find_target,
+    env::state::AppState,
+    models::session::{Session, SessionResponse},
+};
+
+/// Signed in sessions of the user
+pub async fn get(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let user = match find_target(&state, &id).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    match Session::find_active_by_user(&state.db, user.id.unwrap()).await {
+        Ok(sessions) => (
+            StatusCode::OK,
+            Json(
+                sessions
+                    .iter()
+                    .map(|session| session.to_response(auth.session_id))
+                    .collect::<Vec<SessionResponse>>(),
+            ),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("Failed to get sessions of user: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to get sessions" })),
+            )
+                .into_response()
+        }
+    }
+}
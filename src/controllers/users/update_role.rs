@@ -0,0 +1,78 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use bson::doc;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    auth::{guard::AuthUser, permission::Permission},
+    controllers::users::get::find_target,
+    env::state::AppState,
+    models::{
+        audit_log::{AuditAction, AuditLog},
+        user::{User, UserRole},
+    },
+};
+
+/// Unknown roles are refused when deserializing
+#[derive(Deserialize)]
+pub struct UpdateRolePayload {
+    pub role: UserRole,
+}
+
+pub async fn patch(
+    auth: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateRolePayload>,
+) -> impl IntoResponse {
+    if let Err(rejection) = auth.require_permission(Permission::UserManage) {
+        return rejection.into_response();
+    }
+
+    let user = match find_target(&state, &id).await {
+        Ok(user) => user,
+        Err(response) => return response,
+    };
+
+    // Keeps Root from locking themselves out
+    if user.id == auth.user.id {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "You can't change your own role" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = User::update_role(&state.db, user.id.unwrap(), &payload.role).await {
+        log::error!("Failed to update role: {}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "message": "Failed to update role" })),
+        )
+            .into_response();
+    }
+
+    if let Err(e) = AuditLog::record(
+        &state.db,
+        auth.user.id.unwrap(),
+        AuditAction::UserRoleChange,
+        user.id.unwrap(),
+        doc! {"from": user.role.to_string(), "to": payload.role.to_string()},
+    )
+    .await
+    {
+        log::error!("Failed to record role change: {}", e);
+    }
+
+    let user = User {
+        role: payload.role,
+        ..user
+    };
+
+    (StatusCode::OK, Json(user.to_response())).into_response()
+}
@@ -1,6 +1,20 @@
-use mongodb::{Client, Database};
+use crate::models::{
+    api_token::ApiToken, audit_log::AuditLog, ban::Ban, comment::Comment,
+    comment_digest::CommentDigest, idempotency_key::IdempotencyKey, identity::Identity,
+    indieauth_state::IndieAuthState, invite::Invite, login_attempt::LoginAttempt,
+    oauth_state::OAuthState, passkey_credential::PasskeyCredential,
+    password_reset_token::PasswordResetToken, refresh_token::RefreshToken,
+    revoked_token::RevokedToken, session::Session, user::User,
+    webauthn_challenge::WebauthnChallenge,
+};
+use mongodb::{
+    error::{ErrorKind, WriteFailure},
+    Client, Database,
+};
 use std::env;
 
+const DUPLICATE_KEY_ERROR_CODE: i32 = 11000;
+
 pub async fn init_db() -> mongodb::error::Result<Database> {
     let host = env::var("MONGO_HOST").expect("MONGO_HOST must be set");
     let port = env::var("MONGO_PORT").expect("MONGO_PORT must be set");
@@ -13,3 +27,60 @@ pub async fn init_db() -> mongodb::error::Result<Database> {
     let client = Client::with_uri_str(&uri).await?;
     Ok(client.database(database_name.as_str()))
 }
+
+/// Create the indexes of every model
+///
+/// A model failing, such as a unique index over existing duplicates, doesn't
+/// keep the others from being created. Returns the models that failed.
+pub async fn create_indexes(db: &Database) -> Vec<&'static str> {
+    let results = [
+        ("ApiToken", ApiToken::create_indexes(db).await),
+        ("AuditLog", AuditLog::create_indexes(db).await),
+        ("Comment", Comment::create_indexes(db).await),
+        ("CommentDigest", CommentDigest::create_indexes(db).await),
+        ("Ban", Ban::create_indexes(db).await),
+        ("IdempotencyKey", IdempotencyKey::create_indexes(db).await),
+        ("Identity", Identity::create_indexes(db).await),
+        ("IndieAuthState", IndieAuthState::create_indexes(db).await),
+        ("Invite", Invite::create_indexes(db).await),
+        ("LoginAttempt", LoginAttempt::create_indexes(db).await),
+        ("OAuthState", OAuthState::create_indexes(db).await),
+        (
+            "PasskeyCredential",
+            PasskeyCredential::create_indexes(db).await,
+        ),
+        (
+            "PasswordResetToken",
+            PasswordResetToken::create_indexes(db).await,
+        ),
+        ("RefreshToken", RefreshToken::create_indexes(db).await),
+        ("RevokedToken", RevokedToken::create_indexes(db).await),
+        ("Session", Session::create_indexes(db).await),
+        ("User", User::create_indexes(db).await),
+        (
+            "WebauthnChallenge",
+            WebauthnChallenge::create_indexes(db).await,
+        ),
+    ];
+
+    results
+        .into_iter()
+        .filter_map(|(model, result)| {
+            let e = result.err()?;
+            log::error!("Failed to create indexes of {}: {}", model, e);
+            Some(model)
+        })
+        .collect()
+}
+
+/// Whether the error was caused by a unique index violation
+pub fn is_duplicate_key_error(error: &mongodb::error::Error) -> bool {
+    match *error.kind {
+        ErrorKind::Write(WriteFailure::WriteError(ref e)) => e.code == DUPLICATE_KEY_ERROR_CODE,
+        ErrorKind::InsertMany(ref e) => e
+            .write_errors
+            .as_ref()
+            .is_some_and(|errors| errors.iter().any(|e| e.code == DUPLICATE_KEY_ERROR_CODE)),
+        _ => false,
+    }
+}
@@ -1,11 +1,38 @@
 use std::borrow::Cow;
 
+use crate::{
+    auth::{guard::TokenSource, keyring::Algorithm, oauth::OAuthProvider, signup::SignupMode},
+    utils::password::PasswordPolicy,
+};
+
 #[derive(Clone, Debug)]
 pub struct Env {
     pub port: u16,
     pub host: Cow<'static, str>,
+    pub trusted_domains: Vec<String>,
+    pub trusted_proxies: usize,
     pub jwt_secret: Cow<'static, str>,
+    pub jwt_previous_secrets: Vec<String>,
+    pub jwt_signing_key_file: Option<String>,
+    pub jwt_verification_key_files: Vec<String>,
+    pub jwt_algorithms: Vec<Algorithm>,
+    pub jwt_issuer: Cow<'static, str>,
+    pub jwt_audience: Cow<'static, str>,
     pub cookie_domain: Cow<'static, str>,
+    pub comment_min_fill_seconds: i64,
+    pub comment_require_form_token: bool,
+    pub ip_hash_salt: Cow<'static, str>,
+    pub comment_fingerprint_retention_days: i64,
+    pub comment_duplicate_window_seconds: i64,
+    pub protected_names: Vec<String>,
+    pub tripcode_secret: Cow<'static, str>,
+    pub auth_token_precedence: Vec<TokenSource>,
+    pub signup_mode: SignupMode,
+    pub password_policy: PasswordPolicy,
+    pub webauthn_origin: Option<String>,
+    pub oauth_providers: Vec<OAuthProvider>,
+    pub oauth_redirect_base: Cow<'static, str>,
+    pub oauth_success_redirect: Cow<'static, str>,
 }
 
 impl Env {
@@ -18,20 +45,182 @@ impl Env {
             Ok(host) => Cow::Owned(host),
             Err(_) => Cow::Owned("http://localhost/".to_string()),
         };
+        let trusted_domains = std::env::var("TRUSTED_DOMAINS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|domain| domain.trim().trim_end_matches('/').to_string())
+            .filter(|domain| !domain.is_empty())
+            .collect();
+        // Without proxies the socket address is the client, as trusting
+        // `x-forwarded-for` would let clients pick their own address
+        let trusted_proxies = match std::env::var("TRUSTED_PROXY_COUNT") {
+            Ok(count) if !count.is_empty() => count
+                .trim()
+                .parse()
+                .unwrap_or_else(|e| panic!("TRUSTED_PROXY_COUNT: {}", e)),
+            _ => 0,
+        };
         let jwt_secret = match std::env::var("JWT_SECRET") {
-            Ok(jwt_secret) => Cow::Owned(jwt_secret),
+            Ok(jwt_secret) => Cow::<str>::Owned(jwt_secret),
             Err(_) => panic!("JWT_SECRET is not set"),
         };
+        let jwt_previous_secrets = std::env::var("JWT_PREVIOUS_SECRETS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|secret| secret.trim().to_string())
+            .filter(|secret| !secret.is_empty())
+            .collect();
+        let jwt_signing_key_file = match std::env::var("JWT_SIGNING_KEY_FILE") {
+            Ok(path) if !path.is_empty() => Some(path),
+            _ => None,
+        };
+        let jwt_verification_key_files = std::env::var("JWT_VERIFICATION_KEY_FILES")
+            .unwrap_or_default()
+            .split(',')
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty())
+            .collect();
+        let jwt_algorithms = std::env::var("JWT_ALGORITHMS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|algorithm| !algorithm.trim().is_empty())
+            .map(|algorithm| {
+                algorithm
+                    .parse()
+                    .unwrap_or_else(|e| panic!("JWT_ALGORITHMS: {}", e))
+            })
+            .collect();
+        let jwt_issuer = match std::env::var("JWT_ISSUER") {
+            Ok(issuer) if !issuer.is_empty() => Cow::<str>::Owned(issuer),
+            _ => Cow::Owned("marshallku-blog-backend".to_string()),
+        };
+        let jwt_audience = match std::env::var("JWT_AUDIENCE") {
+            Ok(audience) if !audience.is_empty() => Cow::Owned(audience),
+            _ => jwt_issuer.clone(),
+        };
         let cookie_domain = match std::env::var("COOKIE_DOMAIN") {
             Ok(cookie_domain) => Cow::Owned(cookie_domain),
             Err(_) => Cow::Owned("localhost".to_string()),
         };
+        let comment_min_fill_seconds = match std::env::var("COMMENT_MIN_FILL_SECONDS") {
+            Ok(seconds) => seconds.parse().unwrap_or(3),
+            Err(_) => 3,
+        };
+        let comment_require_form_token = match std::env::var("COMMENT_REQUIRE_FORM_TOKEN") {
+            Ok(require) => require != "false",
+            Err(_) => true,
+        };
+        let ip_hash_salt = match std::env::var("IP_HASH_SALT") {
+            Ok(ip_hash_salt) if !ip_hash_salt.is_empty() => Cow::Owned(ip_hash_salt),
+            _ => {
+                log::warn!(
+                    "IP_HASH_SALT is not set, IP hashes are derived from JWT_SECRET and change when it's rotated"
+                );
+                jwt_secret.clone()
+            }
+        };
+        let comment_fingerprint_retention_days =
+            match std::env::var("COMMENT_FINGERPRINT_RETENTION_DAYS") {
+                Ok(days) => days.parse().unwrap_or(90),
+                Err(_) => 90,
+            };
+        let comment_duplicate_window_seconds =
+            match std::env::var("COMMENT_DUPLICATE_WINDOW_SECONDS") {
+                Ok(seconds) => seconds.parse().unwrap_or(600),
+                Err(_) => 600,
+            };
+        let protected_names = std::env::var("PROTECTED_NAMES")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+        let tripcode_secret = match std::env::var("TRIPCODE_SECRET") {
+            Ok(tripcode_secret) if !tripcode_secret.is_empty() => Cow::Owned(tripcode_secret),
+            _ => {
+                log::warn!(
+                    "TRIPCODE_SECRET is not set, tripcodes are derived from JWT_SECRET and change when it's rotated"
+                );
+                jwt_secret.clone()
+            }
+        };
+        let auth_token_precedence = std::env::var("AUTH_TOKEN_PRECEDENCE")
+            .unwrap_or_else(|_| "bearer,cookie".to_string())
+            .split(',')
+            .filter(|source| !source.trim().is_empty())
+            .map(|source| {
+                source
+                    .parse()
+                    .unwrap_or_else(|e| panic!("AUTH_TOKEN_PRECEDENCE: {}", e))
+            })
+            .collect();
+        let signup_mode = match std::env::var("SIGNUP_MODE") {
+            Ok(mode) if !mode.is_empty() => mode
+                .parse()
+                .unwrap_or_else(|e| panic!("SIGNUP_MODE: {}", e)),
+            _ => SignupMode::Open,
+        };
+        let password_policy = PasswordPolicy {
+            min_length: match std::env::var("PASSWORD_MIN_LENGTH") {
+                Ok(length) => length.parse().unwrap_or(8),
+                Err(_) => 8,
+            },
+            min_character_classes: match std::env::var("PASSWORD_MIN_CHARACTER_CLASSES") {
+                Ok(classes) => classes.parse().unwrap_or(1),
+                Err(_) => 1,
+            },
+            reject_common: match std::env::var("PASSWORD_REJECT_COMMON") {
+                Ok(reject_common) => reject_common != "false",
+                Err(_) => true,
+            },
+        };
+        let webauthn_origin = match std::env::var("WEBAUTHN_ORIGIN") {
+            Ok(origin) if !origin.is_empty() => Some(origin),
+            _ => None,
+        };
+        let oauth_providers = std::env::var("OAUTH_PROVIDERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .map(|name| OAuthProvider::from_env(&name))
+            .collect();
+        let oauth_redirect_base = match std::env::var("OAUTH_REDIRECT_BASE") {
+            Ok(base) if !base.is_empty() => Cow::Owned(base.trim_end_matches('/').to_string()),
+            _ => Cow::Owned(format!("http://localhost:{}", port)),
+        };
+        let oauth_success_redirect = match std::env::var("OAUTH_SUCCESS_REDIRECT") {
+            Ok(url) if !url.is_empty() => Cow::Owned(url),
+            _ => Cow::Owned("/".to_string()),
+        };
 
         Self {
             port,
             host,
+            trusted_domains,
+            trusted_proxies,
             jwt_secret,
+            jwt_previous_secrets,
+            jwt_signing_key_file,
+            jwt_verification_key_files,
+            jwt_algorithms,
+            jwt_issuer,
+            jwt_audience,
             cookie_domain,
+            comment_min_fill_seconds,
+            comment_require_form_token,
+            ip_hash_salt,
+            comment_fingerprint_retention_days,
+            comment_duplicate_window_seconds,
+            protected_names,
+            tripcode_secret,
+            auth_token_precedence,
+            signup_mode,
+            password_policy,
+            webauthn_origin,
+            oauth_providers,
+            oauth_redirect_base,
+            oauth_success_redirect,
         }
     }
 }
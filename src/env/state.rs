@@ -1,16 +1,55 @@
-use crate::database::init_db;
+use std::sync::Arc;
+
+use crate::{
+    auth::{
+        guard::TokenSource,
+        keyring::{Key, Keyring},
+        oauth::OAuthProvider,
+        passkey,
+        signup::SignupMode,
+        token::TokenConfig,
+    },
+    database::init_db,
+    models::{ban::BanCache, user::UserNameCache},
+    utils::password::PasswordPolicy,
+};
 
 use super::app::Env;
 use dotenv::dotenv;
 use mongodb::Database;
+use webauthn_rs::Webauthn;
 
 #[derive(Clone)]
 pub struct AppState {
     pub host: String,
     pub port: u16,
+    pub trusted_domains: Vec<String>,
+    /// Reverse proxies in front of the server, set with `TRUSTED_PROXY_COUNT`
+    /// when deployed behind one so clients are read from `x-forwarded-for`
+    pub trusted_proxies: usize,
     pub db: Database,
     pub jwt_secret: String,
+    /// Secrets `JWT_SECRET` replaced, still accepted for the short-lived
+    /// tokens signed with it
+    pub jwt_previous_secrets: Vec<String>,
+    pub token_config: TokenConfig,
     pub cookie_domain: String,
+    pub comment_min_fill_seconds: i64,
+    pub comment_require_form_token: bool,
+    pub ip_hash_salt: String,
+    pub comment_fingerprint_retention_days: i64,
+    pub comment_duplicate_window_seconds: i64,
+    pub protected_names: Vec<String>,
+    pub tripcode_secret: String,
+    pub auth_token_precedence: Vec<TokenSource>,
+    pub signup_mode: SignupMode,
+    pub password_policy: PasswordPolicy,
+    pub webauthn: Arc<Webauthn>,
+    pub oauth_providers: Vec<OAuthProvider>,
+    pub oauth_redirect_base: String,
+    pub oauth_success_redirect: String,
+    pub bans: BanCache,
+    pub user_names: UserNameCache,
 }
 
 impl AppState {
@@ -19,13 +58,87 @@ impl AppState {
 
         let env = Env::new();
         let db = init_db().await?;
+        let webauthn = passkey::relying_party(&env.cookie_domain, env.webauthn_origin.as_deref())
+            .unwrap_or_else(|e| panic!("Invalid WebAuthn configuration: {}", e));
+        let token_config = token_config(&env);
 
         Ok(Self {
             host: env.host.into_owned(),
             port: env.port,
+            trusted_domains: env.trusted_domains,
+            trusted_proxies: env.trusted_proxies,
             db,
+            token_config,
             jwt_secret: env.jwt_secret.into_owned(),
+            jwt_previous_secrets: env.jwt_previous_secrets,
             cookie_domain: env.cookie_domain.into_owned(),
+            comment_min_fill_seconds: env.comment_min_fill_seconds,
+            comment_require_form_token: env.comment_require_form_token,
+            ip_hash_salt: env.ip_hash_salt.into_owned(),
+            comment_fingerprint_retention_days: env.comment_fingerprint_retention_days,
+            comment_duplicate_window_seconds: env.comment_duplicate_window_seconds,
+            protected_names: env.protected_names,
+            tripcode_secret: env.tripcode_secret.into_owned(),
+            auth_token_precedence: env.auth_token_precedence,
+            signup_mode: env.signup_mode,
+            password_policy: env.password_policy,
+            webauthn: Arc::new(webauthn),
+            oauth_providers: env.oauth_providers,
+            oauth_redirect_base: env.oauth_redirect_base.into_owned(),
+            oauth_success_redirect: env.oauth_success_redirect.into_owned(),
+            bans: BanCache::default(),
+            user_names: UserNameCache::default(),
+        })
+    }
+}
+
+impl AppState {
+    /// Secrets the HMAC tokens, such as form and CSRF tokens, are verified
+    /// with, the one they're signed with first
+    pub fn jwt_secrets(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.jwt_secret.as_str())
+            .chain(self.jwt_previous_secrets.iter().map(String::as_str))
+    }
+}
+
+/// Keys of the access tokens
+///
+/// A PEM key in `JWT_SIGNING_KEY_FILE` takes over signing from `JWT_SECRET`,
+/// which keeps verifying the tokens it signed.
+fn token_config(env: &Env) -> TokenConfig {
+    let load = |path: &String| {
+        Key::from_pem_file(path).unwrap_or_else(|e| panic!("Invalid JWT key {}: {}", path, e))
+    };
+    let mut keys: Vec<Key> = env.jwt_signing_key_file.iter().map(load).collect();
+    keys.push(Key::from_secret(&env.jwt_secret));
+    keys.extend(env.jwt_previous_secrets.iter().map(|s| Key::from_secret(s)));
+    keys.extend(env.jwt_verification_key_files.iter().map(load));
+
+    // Default to the algorithms of the configured keys
+    let algorithms = if env.jwt_algorithms.is_empty() {
+        keys.iter().fold(Vec::new(), |mut algorithms, key| {
+            if !algorithms.contains(&key.algorithm) {
+                algorithms.push(key.algorithm);
+            }
+            algorithms
         })
+    } else {
+        env.jwt_algorithms.clone()
+    };
+
+    let signing_key = keys.remove(0);
+
+    if !algorithms.contains(&signing_key.algorithm) {
+        panic!(
+            "JWT_ALGORITHMS doesn't allow {}, the algorithm of the signing key",
+            signing_key.algorithm.as_str()
+        );
+    }
+
+    TokenConfig {
+        keyring: Keyring::new(signing_key, keys).unwrap_or_else(|e| panic!("{}", e)),
+        algorithms,
+        issuer: env.jwt_issuer.to_string(),
+        audience: env.jwt_audience.to_string(),
     }
 }
@@ -1,9 +1,10 @@
-use std::env::var;
+use std::net::SocketAddr;
 
 use axum::{
     http::{HeaderName, HeaderValue},
     serve,
 };
+use constants::auth::CSRF_HEADER;
 use controllers::app::app;
 use env::state::AppState;
 use reqwest::Method;
@@ -12,17 +13,20 @@ use tower_http::{
     cors::CorsLayer,
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
 };
-use tracing::{info, Level};
+use tracing::{error, info, Level};
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter};
 use utils::log::trace_layer_on_request;
 
 mod auth;
+mod cli;
 mod constants;
 mod controllers;
 mod database;
 mod env;
+mod middleware;
 mod models;
+mod tasks;
 mod utils;
 
 fn setup_tracing() {
@@ -42,16 +46,29 @@ async fn main() {
     setup_tracing();
 
     let state = AppState::new().await.unwrap();
+
+    if cli::run(&state).await {
+        return;
+    }
+
+    let failed_models = database::create_indexes(&state.db).await;
+
+    if !failed_models.is_empty() {
+        error!(
+            "Indexes of {} are missing, their uniqueness and expiry aren't enforced",
+            failed_models.join(", ")
+        );
+    }
+
+    tasks::bans::spawn(state.clone());
+    tasks::purge::spawn(state.clone());
+    tasks::user_names::spawn(state.clone());
+
     let address = format!("{}:{}", state.host, state.port);
-    let trusted_domains = var("TRUSTED_DOMAINS").unwrap_or_default();
-    let origins = trusted_domains
-        .split(',')
-        .map(|domain| domain.trim())
-        .filter(|domain| !domain.is_empty())
-        .filter_map(|domain| match HeaderValue::from_str(&domain.to_string()) {
-            Ok(value) => Some(value),
-            Err(_) => None,
-        })
+    let origins = state
+        .trusted_domains
+        .iter()
+        .filter_map(|domain| HeaderValue::from_str(domain).ok())
         .collect::<Vec<_>>();
     let cors_layer = CorsLayer::new()
         .allow_credentials(true)
@@ -60,6 +77,8 @@ async fn main() {
             HeaderName::from_static("authorization"),
             HeaderName::from_static("accept"),
             HeaderName::from_static("origin"),
+            HeaderName::from_static("idempotency-key"),
+            HeaderName::from_static(CSRF_HEADER),
         ])
         .allow_methods(vec![
             Method::GET,
@@ -69,7 +88,7 @@ async fn main() {
             Method::DELETE,
         ])
         .allow_origin(origins);
-    let app = app()
+    let app = app(state.clone())
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
@@ -82,10 +101,13 @@ async fn main() {
 
     info!("Listening on http://{}", address);
 
-    serve(listener, app.into_make_service())
-        .with_graceful_shutdown(handle_shutdown())
-        .await
-        .unwrap();
+    serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(handle_shutdown())
+    .await
+    .unwrap();
 }
 
 async fn handle_shutdown() {
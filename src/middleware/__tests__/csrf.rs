@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{
+            header::{AUTHORIZATION, COOKIE, ORIGIN},
+            Request, StatusCode,
+        },
+        middleware::from_fn_with_state,
+        routing::post,
+        Router,
+    };
+    use bson::oid::ObjectId;
+    use chrono::Utc;
+    use tower::ServiceExt;
+
+    use crate::{
+        auth::{csrf::issue, guard::TokenSource, token::Token},
+        constants::auth::{
+            CSRF_COOKIE_KEY, CSRF_HEADER, TOKEN_COOKIE_KEY, VERIFIED_SITE_COOKIE_KEY,
+        },
+        env::state::AppState,
+        middleware::csrf,
+        models::user::{User, UserRole},
+    };
+
+    const TRUSTED_ORIGIN: &str = "https://blog.example";
+
+    async fn state() -> AppState {
+        let mut state = AppState::new().await.unwrap();
+
+        state.trusted_domains = vec![TRUSTED_ORIGIN.to_string()];
+        state.auth_token_precedence = vec![TokenSource::Bearer, TokenSource::Cookie];
+
+        state
+    }
+
+    async fn send(state: &AppState, request: Request<Body>) -> StatusCode {
+        let app: Router<AppState> = Router::new()
+            .route("/", post(|| async { StatusCode::OK }))
+            .layer(from_fn_with_state(state.clone(), csrf::enforce));
+
+        app.with_state(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    /// Access token of a session, with the session id
+    fn access_token(state: &AppState) -> (String, String) {
+        let user = User {
+            id: Some(ObjectId::new()),
+            name: "user".to_string(),
+            email: None,
+            password: String::new(),
+            role: UserRole::User,
+            sessions_valid_after: None,
+            totp: None,
+            disabled_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let session_id = ObjectId::new();
+        let token = Token::from_user(&user, session_id, &state.token_config).unwrap();
+
+        (token, session_id.to_hex())
+    }
+
+    fn request(cookies: &[(&str, &str)], csrf_token: Option<&str>) -> axum::http::request::Builder {
+        let mut request = Request::builder().method("POST").uri("/");
+        let cookies = cookies
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<String>>()
+            .join("; ");
+
+        if !cookies.is_empty() {
+            request = request.header(COOKIE, cookies);
+        }
+        if let Some(csrf_token) = csrf_token {
+            request = request.header(CSRF_HEADER, csrf_token);
+        }
+
+        request
+    }
+
+    #[tokio::test]
+    async fn should_let_requests_without_credential_cookies_through() {
+        let state = state().await;
+        let request = request(&[], None).body(Body::empty()).unwrap();
+
+        assert_eq!(send(&state, request).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn should_let_bearer_requests_through() {
+        let state = state().await;
+        let (token, _) = access_token(&state);
+        let request = request(&[(TOKEN_COOKIE_KEY, &token)], None)
+            .header(AUTHORIZATION, format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(send(&state, request).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn should_accept_token_of_the_session() {
+        let state = state().await;
+        let (token, session_id) = access_token(&state);
+        let csrf_token = issue(&state.jwt_secret, Some(&session_id));
+        let request = request(
+            &[(TOKEN_COOKIE_KEY, &token), (CSRF_COOKIE_KEY, &csrf_token)],
+            Some(&csrf_token),
+        )
+        .header(ORIGIN, TRUSTED_ORIGIN)
+        .body(Body::empty())
+        .unwrap();
+
+        assert_eq!(send(&state, request).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn should_reject_foreign_origin() {
+        let state = state().await;
+        let (token, session_id) = access_token(&state);
+        let csrf_token = issue(&state.jwt_secret, Some(&session_id));
+        let request = request(
+            &[(TOKEN_COOKIE_KEY, &token), (CSRF_COOKIE_KEY, &csrf_token)],
+            Some(&csrf_token),
+        )
+        .header(ORIGIN, "https://evil.example")
+        .body(Body::empty())
+        .unwrap();
+
+        assert_eq!(send(&state, request).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn should_reject_token_of_another_session() {
+        let state = state().await;
+        let (token, _) = access_token(&state);
+        let csrf_token = issue(&state.jwt_secret, Some(&ObjectId::new().to_hex()));
+        let request = request(
+            &[(TOKEN_COOKIE_KEY, &token), (CSRF_COOKIE_KEY, &csrf_token)],
+            Some(&csrf_token),
+        )
+        .body(Body::empty())
+        .unwrap();
+
+        assert_eq!(send(&state, request).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn should_reject_session_cookies_without_session() {
+        let state = state().await;
+        let csrf_token = issue(&state.jwt_secret, None);
+        let request = request(
+            &[
+                (TOKEN_COOKIE_KEY, "expired"),
+                (CSRF_COOKIE_KEY, &csrf_token),
+            ],
+            Some(&csrf_token),
+        )
+        .body(Body::empty())
+        .unwrap();
+
+        assert_eq!(send(&state, request).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn should_accept_unbound_token_of_verified_site() {
+        let state = state().await;
+        let csrf_token = issue(&state.jwt_secret, None);
+        let request = request(
+            &[
+                (VERIFIED_SITE_COOKIE_KEY, "proof"),
+                (CSRF_COOKIE_KEY, &csrf_token),
+            ],
+            Some(&csrf_token),
+        )
+        .body(Body::empty())
+        .unwrap();
+
+        assert_eq!(send(&state, request).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,71 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{env::state::AppState, models::ban::BanTarget, utils::client::ClientInfo};
+
+/// Same as the default body limit of the `Json` extractor
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Added to the request extensions when the client is shadow banned
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowBanned;
+
+#[derive(Deserialize, Default)]
+struct BanIdentity {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+/// Reject write requests from banned IPs, emails and names
+///
+/// Shadow banned clients are let through with [`ShadowBanned`] attached, so
+/// handlers can hide what they write from everyone else.
+pub async fn enforce(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let client = ClientInfo::from_http(
+        request.headers(),
+        request.extensions(),
+        state.trusted_proxies,
+    );
+    let (parts, body) = request.into_parts();
+    let bytes = match to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(json!({ "message": "Request body is too large" })),
+            )
+                .into_response();
+        }
+    };
+    let identity = serde_json::from_slice::<BanIdentity>(&bytes).unwrap_or_default();
+    let ban = state.bans.find_match(&BanTarget {
+        ip: client.ip.as_deref(),
+        email: identity.email.as_deref(),
+        name: identity.name.as_deref(),
+    });
+    let mut request = Request::from_parts(parts, Body::from(bytes));
+
+    match ban {
+        None => next.run(request).await,
+        Some(ban) if ban.shadow => {
+            request.extensions_mut().insert(ShadowBanned);
+            next.run(request).await
+        }
+        Some(ban) => {
+            log::info!("[Ban] Rejected request matching {} ban", ban.value);
+            (
+                StatusCode::FORBIDDEN,
+                Json(json!({ "message": "You are not allowed to perform this action" })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{ORIGIN, REFERER, SET_COOKIE},
+        HeaderMap, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use axum_extra::extract::cookie::CookieJar;
+use reqwest::Url;
+use serde_json::json;
+
+use crate::{
+    auth::{
+        cookie::removal_cookies,
+        csrf,
+        guard::{extract_token, TokenSource},
+        session,
+    },
+    constants::auth::{
+        CSRF_COOKIE_KEY, CSRF_HEADER, REFRESH_TOKEN_COOKIE_KEY, TOKEN_COOKIE_KEY,
+        VERIFIED_SITE_COOKIE_KEY,
+    },
+    env::state::AppState,
+};
+
+/// Cookies the browser attaches on its own, which a forged request could
+/// ride on
+const CREDENTIAL_COOKIES: [&str; 3] = [
+    TOKEN_COOKIE_KEY,
+    REFRESH_TOKEN_COOKIE_KEY,
+    VERIFIED_SITE_COOKIE_KEY,
+];
+
+/// Reject cross-site writes made with cookies
+///
+/// Unsafe requests carrying credential cookies must come from a trusted
+/// origin when the browser tells where they come from, and echo the
+/// `csrf-token` cookie in the `X-CSRF-Token` header, issued for the session
+/// of the cookies. Session cookies that don't lead to a session are
+/// rejected, so only visitors who aren't signed in can use tokens bound to
+/// no session. Requests the guards authenticate with the
+/// `Authorization` header can't be forged by another site, so they are let
+/// through.
+pub async fn enforce(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let headers = request.headers();
+    let is_bearer = matches!(
+        extract_token(headers, &state.auth_token_precedence),
+        Some((TokenSource::Bearer, _))
+    );
+
+    if request.method().is_safe() || is_bearer {
+        return next.run(request).await;
+    }
+
+    let cookie_jar = CookieJar::from_headers(headers);
+
+    if !CREDENTIAL_COOKIES
+        .iter()
+        .any(|key| cookie_jar.get(key).is_some())
+    {
+        return next.run(request).await;
+    }
+
+    if let Some(origin) = request_origin(headers) {
+        if !state.trusted_domains.contains(&origin) {
+            log::info!("[CSRF] Rejected request from {}", origin);
+            return reject("Cross-site request is not allowed");
+        }
+    }
+
+    let cookie = cookie_jar.get(CSRF_COOKIE_KEY).map(|cookie| cookie.value());
+    let header = headers
+        .get(CSRF_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    let token_session_id = match (cookie, header) {
+        (Some(cookie), Some(header)) if csrf::matches(cookie, header) => state
+            .jwt_secrets()
+            .find_map(|secret| csrf::verify(cookie, secret)),
+        _ => None,
+    };
+    let Some(token_session_id) = token_session_id else {
+        return reject("Missing or invalid CSRF token");
+    };
+
+    match session::from_cookies(&state, &cookie_jar).await {
+        Ok(Some(session_id)) if session_id != token_session_id => {
+            reject("CSRF token was issued for another session")
+        }
+        Ok(Some(_)) => next.run(request).await,
+        // Session cookies that don't lead to a session can't be checked
+        // against the token, so they are dropped for the next request. The
+        // verified website of a visitor who isn't signed in isn't bound to
+        // anything.
+        Ok(None) if has_session_cookies(&cookie_jar) => {
+            let mut response = reject("Session has expired, please sign in again");
+
+            for cookie in removal_cookies(state.cookie_domain.clone()) {
+                response
+                    .headers_mut()
+                    .append(SET_COOKIE, cookie.to_string().parse().unwrap());
+            }
+
+            response
+        }
+        Ok(None) => next.run(request).await,
+        Err(e) => {
+            log::error!("[CSRF] Failed to find session of request: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "message": "Failed to check CSRF token" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Whether the request carries cookies of a signed in session
+fn has_session_cookies(cookie_jar: &CookieJar) -> bool {
+    [TOKEN_COOKIE_KEY, REFRESH_TOKEN_COOKIE_KEY]
+        .iter()
+        .any(|key| cookie_jar.get(key).is_some())
+}
+
+/// Origin of the page that sent the request, from `Origin` or else `Referer`
+///
+/// `None` when the browser sent neither, opaque origins such as `null` are
+/// kept so they get rejected.
+fn request_origin(headers: &HeaderMap) -> Option<String> {
+    if let Some(origin) = headers.get(ORIGIN) {
+        return Some(origin.to_str().unwrap_or_default().to_string());
+    }
+
+    headers
+        .get(REFERER)
+        .and_then(|value| value.to_str().ok())
+        .map(|referer| match Url::parse(referer) {
+            Ok(url) => url.origin().ascii_serialization(),
+            Err(_) => "null".to_string(),
+        })
+}
+
+fn reject(message: &'static str) -> Response {
+    (StatusCode::FORBIDDEN, Json(json!({ "message": message }))).into_response()
+}
@@ -0,0 +1,4 @@
+mod __tests__;
+
+pub mod ban;
+pub mod csrf;
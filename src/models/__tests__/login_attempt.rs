@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        constants::auth::{LOGIN_LOCKOUT_BASE_IN_SECONDS, LOGIN_LOCKOUT_MAX_IN_SECONDS},
+        models::login_attempt::lockout_seconds,
+    };
+
+    #[test]
+    fn should_not_lock_below_threshold() {
+        assert_eq!(lockout_seconds(0, 5), None);
+        assert_eq!(lockout_seconds(4, 5), None);
+    }
+
+    #[test]
+    fn should_double_lockout_past_threshold() {
+        assert_eq!(lockout_seconds(5, 5), Some(LOGIN_LOCKOUT_BASE_IN_SECONDS));
+        assert_eq!(
+            lockout_seconds(6, 5),
+            Some(LOGIN_LOCKOUT_BASE_IN_SECONDS * 2)
+        );
+        assert_eq!(
+            lockout_seconds(8, 5),
+            Some(LOGIN_LOCKOUT_BASE_IN_SECONDS * 8)
+        );
+    }
+
+    #[test]
+    fn should_cap_lockout() {
+        assert_eq!(lockout_seconds(20, 5), Some(LOGIN_LOCKOUT_MAX_IN_SECONDS));
+        assert_eq!(
+            lockout_seconds(i32::MAX, 5),
+            Some(LOGIN_LOCKOUT_MAX_IN_SECONDS)
+        );
+    }
+}
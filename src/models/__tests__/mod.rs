@@ -0,0 +1,2 @@
+mod login_attempt;
+mod user;
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod tests {
+    use crate::models::user::UserNameCache;
+
+    #[test]
+    fn should_reserve_look_alikes_of_other_users() {
+        let names = UserNameCache::default();
+        names.insert("marshallku");
+
+        assert!(names.is_reserved("marshaIlku", None, &[]));
+        assert!(names.is_reserved("rnarshallku", None, &[]));
+        assert!(!names.is_reserved("marshallku", Some("marshallku"), &[]));
+        assert!(!names.is_reserved("someone", None, &[]));
+    }
+
+    #[test]
+    fn should_reserve_protected_names_for_everyone() {
+        let names = UserNameCache::default();
+        let protected = vec!["admin".to_string()];
+        names.insert("admin");
+
+        assert!(names.is_reserved("admin", Some("admin"), &protected));
+        assert!(names.is_reserved("AdmIn", None, &protected));
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::encryption::{generate_token, hash_token};
+
+const COLLECTION_NAME: &str = "api_tokens";
+
+/// Prefix of API tokens, distinguishes them from JWTs
+pub const API_TOKEN_PREFIX: &str = "mkb_";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum TokenScope {
+    /// Post comments as the owner of the token
+    #[serde(rename = "comments:write")]
+    CommentsWrite,
+    /// Read data about comments that isn't public
+    #[serde(rename = "comments:read")]
+    CommentsRead,
+    /// Edit comments and manage bans
+    #[serde(rename = "comments:moderate")]
+    CommentsModerate,
+    /// Delete comments
+    #[serde(rename = "comments:delete")]
+    CommentsDelete,
+}
+
+impl TokenScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenScope::CommentsWrite => "comments:write",
+            TokenScope::CommentsRead => "comments:read",
+            TokenScope::CommentsModerate => "comments:moderate",
+            TokenScope::CommentsDelete => "comments:delete",
+        }
+    }
+}
+
+/// Long-lived credential for scripts, acting on behalf of its owner
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// Owner of the token
+    #[serde(rename = "userId")]
+    pub user_id: ObjectId,
+
+    /// Name to recognize the token by
+    pub name: String,
+
+    /// SHA-256 hash of the token
+    #[serde(rename = "tokenHash")]
+    pub token_hash: String,
+
+    /// What the token is allowed to do
+    pub scopes: Vec<TokenScope>,
+
+    /// When the token stops working, `None` for tokens that never expire
+    #[serde(
+        rename = "expiresAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    #[serde(
+        rename = "lastUsedAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub last_used_at: Option<DateTime<Utc>>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiTokenResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    pub name: String,
+
+    pub scopes: Vec<TokenScope>,
+
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<String>,
+
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<String>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl ApiToken {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"tokenHash": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! {"userId": 1}).build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Create a token and return it along with the secret
+    ///
+    /// Only the hash of the secret is stored, it can't be shown again.
+    pub async fn create(
+        db: &Database,
+        user_id: ObjectId,
+        name: String,
+        scopes: Vec<TokenScope>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(Self, String), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let secret = format!("{}{}", API_TOKEN_PREFIX, generate_token());
+        let api_token = Self {
+            id: None,
+            user_id,
+            name,
+            token_hash: hash_token(&secret),
+            scopes,
+            expires_at,
+            last_used_at: None,
+            created_at: Utc::now(),
+        };
+
+        let result = collection.insert_one(api_token.clone()).await?;
+        let api_token = Self {
+            id: result.inserted_id.as_object_id(),
+            ..api_token
+        };
+
+        Ok((api_token, secret))
+    }
+
+    pub async fn find_by_user(db: &Database, user_id: ObjectId) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {"userId": user_id})
+            .sort(doc! {"createdAt": -1})
+            .await?;
+        let mut api_tokens: Vec<Self> = Vec::new();
+
+        while let Some(api_token) = cursor.try_next().await? {
+            api_tokens.push(api_token);
+        }
+
+        Ok(api_tokens)
+    }
+
+    /// Find the token matching the secret and record its use
+    ///
+    /// Expired tokens are never returned.
+    pub async fn authenticate(db: &Database, secret: &str) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let now = bson::DateTime::from_chrono(Utc::now());
+        let api_token = collection
+            .find_one_and_update(
+                doc! {
+                    "tokenHash": hash_token(secret),
+                    "$or": [
+                        {"expiresAt": null},
+                        {"expiresAt": {"$gt": now}},
+                    ],
+                },
+                doc! {"$set": {"lastUsedAt": now}},
+            )
+            .await?;
+
+        api_token.ok_or_else(|| Error::custom("API token not found"))
+    }
+
+    /// Delete a token of the user
+    pub async fn revoke(db: &Database, user_id: ObjectId, id: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id =
+            ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid API token ID"))?;
+
+        let result = collection
+            .delete_one(doc! {"_id": object_id, "userId": user_id})
+            .await?;
+
+        if result.deleted_count == 0 {
+            return Err(Error::custom("API token not found"));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_response(&self) -> ApiTokenResponse {
+        ApiTokenResponse {
+            id: self.id.unwrap().to_string(),
+            name: self.name.clone(),
+            scopes: self.scopes.clone(),
+            expires_at: self.expires_at.map(|date| date.to_rfc3339()),
+            last_used_at: self.last_used_at.map(|date| date.to_rfc3339()),
+            created_at: self.created_at.to_rfc3339(),
+        }
+    }
+
+    /// Revoke the API tokens of a deleted user
+    pub async fn delete_by_user(db: &Database, user_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.delete_many(doc! {"userId": user_id}).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    error::Error,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+const COLLECTION_NAME: &str = "audit_logs";
+
+/// Action recorded in the audit log, only user administration for now
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum AuditAction {
+    #[serde(rename = "user.role_change")]
+    UserRoleChange,
+    #[serde(rename = "user.disable")]
+    UserDisable,
+    #[serde(rename = "user.enable")]
+    UserEnable,
+    #[serde(rename = "user.delete")]
+    UserDelete,
+}
+
+/// Administrative action, kept as long as the database
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLog {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// User who took the action
+    #[serde(rename = "actorId")]
+    pub actor_id: ObjectId,
+
+    pub action: AuditAction,
+
+    /// User the action was taken on
+    #[serde(rename = "targetId")]
+    pub target_id: ObjectId,
+
+    /// Data about the action, such as the previous and new role
+    #[serde(default)]
+    pub details: Document,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLogResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    #[serde(rename = "actorId")]
+    pub actor_id: String,
+
+    pub action: AuditAction,
+
+    #[serde(rename = "targetId")]
+    pub target_id: String,
+
+    pub details: Document,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl AuditLog {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder().keys(doc! {"createdAt": -1}).build(),
+            IndexModel::builder().keys(doc! {"targetId": 1}).build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    pub async fn record(
+        db: &Database,
+        actor_id: ObjectId,
+        action: AuditAction,
+        target_id: ObjectId,
+        details: Document,
+    ) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .insert_one(Self {
+                id: None,
+                actor_id,
+                action,
+                target_id,
+                details,
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Entries about `target_id`, or all of them, newest first
+    pub async fn find_page(
+        db: &Database,
+        target_id: Option<ObjectId>,
+        skip: u64,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let filter = match target_id {
+            Some(target_id) => doc! {"targetId": target_id},
+            None => doc! {},
+        };
+        let mut cursor = collection
+            .find(filter)
+            .sort(doc! {"createdAt": -1})
+            .skip(skip)
+            .limit(limit)
+            .await?;
+        let mut audit_logs: Vec<Self> = Vec::new();
+
+        while let Some(audit_log) = cursor.try_next().await? {
+            audit_logs.push(audit_log);
+        }
+
+        Ok(audit_logs)
+    }
+
+    pub fn to_response(&self) -> AuditLogResponse {
+        AuditLogResponse {
+            id: self.id.unwrap().to_hex(),
+            actor_id: self.actor_id.to_hex(),
+            action: self.action,
+            target_id: self.target_id.to_hex(),
+            details: self.details.clone(),
+            created_at: self.created_at.to_rfc3339(),
+        }
+    }
+}
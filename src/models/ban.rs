@@ -0,0 +1,267 @@
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{
+    ip::{cidr_contains, is_same_ip, parse_cidr},
+    pattern::matches_wildcard,
+};
+
+const COLLECTION_NAME: &str = "bans";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BanKind {
+    /// Single IP address
+    Ip,
+    /// Range of IP addresses, e.g. `10.0.0.0/8`
+    Cidr,
+    /// Email pattern, `*` matches any characters
+    Email,
+    /// Name pattern, `*` matches any characters
+    Name,
+}
+
+impl BanKind {
+    /// Whether `value` is well-formed for this kind of ban
+    pub fn is_valid_value(&self, value: &str) -> bool {
+        match self {
+            BanKind::Ip => value.trim().parse::<std::net::IpAddr>().is_ok(),
+            BanKind::Cidr => parse_cidr(value).is_some(),
+            BanKind::Email | BanKind::Name => !value.trim().is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ban {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// What `value` is matched against
+    pub kind: BanKind,
+
+    /// IP address, CIDR range or pattern depending on `kind`
+    pub value: String,
+
+    /// Accept comments from the banned commenter, but only show them to the commenter
+    #[serde(default)]
+    pub shadow: bool,
+
+    /// Note for moderators
+    #[serde(default)]
+    pub reason: String,
+
+    /// When the ban is lifted, `None` for permanent bans
+    #[serde(
+        rename = "expiresAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+
+    /// Last update timestamp, automatically managed
+    #[serde(
+        rename = "updatedAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BanResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    pub kind: BanKind,
+
+    pub value: String,
+
+    pub shadow: bool,
+
+    pub reason: String,
+
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<String>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+/// Who is trying to write, checked against the ban list
+#[derive(Debug, Default)]
+pub struct BanTarget<'a> {
+    pub ip: Option<&'a str>,
+    pub email: Option<&'a str>,
+    pub name: Option<&'a str>,
+}
+
+impl Ban {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        // Let MongoDB remove bans once they expire
+        let index = IndexModel::builder()
+            .keys(doc! {"expiresAt": 1})
+            .options(
+                IndexOptions::builder()
+                    .expire_after(std::time::Duration::from_secs(0))
+                    .build(),
+            )
+            .build();
+
+        collection.create_index(index).await?;
+
+        Ok(())
+    }
+
+    pub async fn create(db: &Database, ban: Self) -> Result<Self, Error> {
+        let collection = db.collection(COLLECTION_NAME);
+        let result = collection.insert_one(ban.clone()).await?;
+        let ban = collection
+            .find_one(doc! {"_id": result.inserted_id})
+            .await?;
+
+        if ban.is_none() {
+            return Err(Error::custom("Failed to create ban"));
+        }
+
+        Ok(ban.unwrap())
+    }
+
+    pub async fn find_all(db: &Database) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {})
+            .sort(doc! {"createdAt": -1})
+            .await?;
+        let mut bans: Vec<Self> = Vec::new();
+
+        while let Some(ban) = cursor.try_next().await? {
+            bans.push(ban);
+        }
+
+        Ok(bans)
+    }
+
+    pub async fn find_by_id(db: &Database, id: &str) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid ban ID"))?;
+        let ban = collection.find_one(doc! {"_id": object_id}).await?;
+
+        if ban.is_none() {
+            return Err(Error::custom("Ban not found"));
+        }
+
+        Ok(ban.unwrap())
+    }
+
+    pub async fn update(db: &Database, id: &str, ban: Self) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid ban ID"))?;
+        let result = collection
+            .replace_one(doc! {"_id": object_id}, Self { id: None, ..ban })
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(Error::custom("Ban not found"));
+        }
+
+        let ban = collection.find_one(doc! {"_id": object_id}).await?;
+
+        ban.ok_or_else(|| Error::custom("Ban not found"))
+    }
+
+    pub async fn delete(db: &Database, id: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid ban ID"))?;
+
+        let result = collection.delete_one(doc! {"_id": object_id}).await?;
+
+        if result.deleted_count == 0 {
+            return Err(Error::custom("Ban not found"));
+        }
+
+        Ok(())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+    }
+
+    pub fn matches(&self, target: &BanTarget) -> bool {
+        if self.is_expired() {
+            return false;
+        }
+
+        match self.kind {
+            BanKind::Ip => target.ip.is_some_and(|ip| is_same_ip(&self.value, ip)),
+            BanKind::Cidr => target.ip.is_some_and(|ip| cidr_contains(&self.value, ip)),
+            BanKind::Email => target
+                .email
+                .is_some_and(|email| matches_wildcard(&self.value, email.trim())),
+            BanKind::Name => target
+                .name
+                .is_some_and(|name| matches_wildcard(&self.value, name.trim())),
+        }
+    }
+
+    pub fn to_response(&self) -> BanResponse {
+        BanResponse {
+            id: self.id.unwrap().to_string(),
+            kind: self.kind.clone(),
+            value: self.value.clone(),
+            shadow: self.shadow,
+            reason: self.reason.clone(),
+            expires_at: self.expires_at.map(|date| date.to_rfc3339()),
+            created_at: self.created_at.to_rfc3339(),
+            updated_at: self.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// In-memory copy of the ban list, shared between requests
+#[derive(Debug, Clone, Default)]
+pub struct BanCache {
+    bans: Arc<RwLock<Vec<Ban>>>,
+}
+
+impl BanCache {
+    pub async fn reload(&self, db: &Database) -> Result<(), Error> {
+        let bans = Ban::find_all(db).await?;
+
+        *self.bans.write().unwrap() = bans;
+
+        Ok(())
+    }
+
+    /// Find a ban matching the target, preferring full bans over shadow bans
+    pub fn find_match(&self, target: &BanTarget) -> Option<Ban> {
+        let bans = self.bans.read().unwrap();
+        let mut matched = bans.iter().filter(|ban| ban.matches(target));
+
+        matched
+            .clone()
+            .find(|ban| !ban.shadow)
+            .or_else(|| matched.next())
+            .cloned()
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Duration, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    Database,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::auth::BOOTSTRAP_CLAIM_TIMEOUT_IN_SECONDS, database::is_duplicate_key_error,
+};
+
+const COLLECTION_NAME: &str = "bootstrap";
+
+/// Id of the single document marking who became the Root user
+const ROOT_MARKER_ID: &str = "root";
+
+/// Marker of the first signup, so concurrent signups on an empty
+/// installation can't all become Root
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bootstrap {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    /// Root user created by the signup that claimed the marker, `None` while
+    /// the signup is in progress
+    #[serde(rename = "userId", default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<ObjectId>,
+
+    #[serde(
+        rename = "claimedAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub claimed_at: DateTime<Utc>,
+}
+
+impl Bootstrap {
+    /// Claim the creation of the Root user, failing if another signup did
+    ///
+    /// Claims of signups that never completed are taken over once they are
+    /// older than `BOOTSTRAP_CLAIM_TIMEOUT_IN_SECONDS`.
+    pub async fn claim_root(db: &Database) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let now = Utc::now();
+        let stale_before = now - Duration::seconds(BOOTSTRAP_CLAIM_TIMEOUT_IN_SECONDS);
+        let result = collection
+            .update_one(
+                doc! {
+                    "_id": ROOT_MARKER_ID,
+                    "userId": {"$exists": false},
+                    "claimedAt": {"$lte": bson::DateTime::from_chrono(stale_before)},
+                },
+                doc! {"$set": {"claimedAt": bson::DateTime::from_chrono(now)}},
+            )
+            .upsert(true)
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) if is_duplicate_key_error(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Record the Root user created after claiming the marker
+    pub async fn complete_root(db: &Database, user_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(
+                doc! {"_id": ROOT_MARKER_ID},
+                doc! {"$set": {"userId": user_id}},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Let another signup claim the marker after this one failed
+    pub async fn release_root(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .delete_one(doc! {"_id": ROOT_MARKER_ID, "userId": {"$exists": false}})
+            .await?;
+
+        Ok(())
+    }
+}
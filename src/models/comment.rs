@@ -1,8 +1,12 @@
 use bson::doc;
 use chrono::{DateTime, Utc};
+use data_encoding::HEXLOWER;
 use futures::TryStreamExt;
-use mongodb::{bson::oid::ObjectId, error::Error, Database};
+use mongodb::{bson::oid::ObjectId, error::Error, options::IndexOptions, Database, IndexModel};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::utils::text::normalize_body;
 
 const COLLECTION_NAME: &str = "comment";
 
@@ -23,6 +27,14 @@ pub struct Comment {
     #[serde(rename = "byPostAuthor", default)]
     pub by_post_author: bool,
 
+    /// Registered user who wrote this comment
+    #[serde(rename = "authorId", skip_serializing_if = "Option::is_none")]
+    pub author_id: Option<ObjectId>,
+
+    /// Tripcode derived from the anonymous commenter's secret
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tripcode: Option<String>,
+
     /// Optional email of the commenter
     #[serde(default)]
     pub email: String,
@@ -31,6 +43,10 @@ pub struct Comment {
     #[serde(default)]
     pub url: String,
 
+    /// Whether the commenter proved they own `url` through IndieAuth
+    #[serde(rename = "verifiedSite", default)]
+    pub verified_site: bool,
+
     /// The comment content
     pub body: String,
 
@@ -57,6 +73,28 @@ pub struct Comment {
     /// Replies to this comment
     #[serde(skip_serializing_if = "Option::is_none")]
     pub replies: Option<Vec<Self>>,
+
+    /// Whether the commenter was shadow banned, only they can see the comment
+    #[serde(rename = "shadowBanned", default)]
+    pub shadow_banned: bool,
+
+    /// Data about the commenter for moderation, never exposed through the API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation: Option<CommentModeration>,
+}
+
+/// Moderation data recorded when a comment is created
+///
+/// Purged once the comment is older than `COMMENT_FINGERPRINT_RETENTION_DAYS`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CommentModeration {
+    /// Salted hash of the commenter's IP address
+    #[serde(rename = "ipHash", skip_serializing_if = "Option::is_none")]
+    pub ip_hash: Option<String>,
+
+    /// User agent of the commenter
+    #[serde(rename = "userAgent", skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
 }
 
 fn default_date() -> DateTime<Utc> {
@@ -77,10 +115,16 @@ pub struct CommentResponse {
     #[serde(rename = "byPostAuthor")]
     pub by_post_author: bool,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tripcode: Option<String>,
+
     pub email: String,
 
     pub url: String,
 
+    #[serde(rename = "verifiedSite")]
+    pub verified_site: bool,
+
     pub body: String,
 
     #[serde(rename = "parentCommentId")]
@@ -101,6 +145,29 @@ fn default_name() -> String {
 }
 
 impl Comment {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"moderation.ipHash": 1})
+                .options(IndexOptions::builder().sparse(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"tripcode": 1})
+                .options(IndexOptions::builder().sparse(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"authorId": 1})
+                .options(IndexOptions::builder().sparse(true).build())
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
     pub async fn create(db: &Database, comment: Self) -> Result<Self, Error> {
         let collection = db.collection(COLLECTION_NAME);
         let result = collection.insert_one(comment.clone()).await?;
@@ -121,23 +188,38 @@ impl Comment {
             name: self.name.clone(),
             post_slug: self.post_slug.clone(),
             by_post_author: self.by_post_author,
+            tripcode: self.tripcode.clone(),
             email: self.email.clone(),
             url: self.url.clone(),
+            verified_site: self.verified_site,
             body: self.body.clone(),
-            parent_comment_id: self.parent_comment_id.clone().map(|id| id.to_string()),
+            parent_comment_id: self.parent_comment_id.map(|id| id.to_string()),
             created_at: self.created_at.to_rfc3339(),
             updated_at: Some(self.updated_at.to_rfc3339()),
             replies: None,
         }
     }
 
-    pub async fn get_by_slug(db: &Database, slug: &str) -> Result<Vec<CommentResponse>, Error> {
+    /// Get comments of a post, including shadow banned comments written from
+    /// `viewer_ip_hash`
+    pub async fn get_by_slug(
+        db: &Database,
+        slug: &str,
+        viewer_ip_hash: Option<&str>,
+    ) -> Result<Vec<CommentResponse>, Error> {
         let collection = db.collection::<Self>(COLLECTION_NAME);
 
         log::info!("Getting comments for slug: {}", slug);
 
+        let visibility = match viewer_ip_hash {
+            Some(ip_hash) => doc! {"$or": [
+                {"shadowBanned": {"$ne": true}},
+                {"moderation.ipHash": ip_hash},
+            ]},
+            None => doc! {"shadowBanned": {"$ne": true}},
+        };
         let mut cursor = collection
-            .find(doc! {"postSlug": slug})
+            .find(doc! {"$and": [{"postSlug": slug}, visibility]})
             .sort(doc! {"createdAt": -1})
             .await?;
         let mut all_comments: Vec<Self> = Vec::new();
@@ -183,7 +265,7 @@ impl Comment {
     pub async fn get_recent(db: &Database, limit: i64) -> Result<Vec<CommentResponse>, Error> {
         let collection = db.collection::<Self>(COLLECTION_NAME);
         let mut cursor = collection
-            .find(doc! {})
+            .find(doc! {"shadowBanned": {"$ne": true}})
             .limit(limit)
             .sort(doc! {"createdAt": -1})
             .await?;
@@ -196,6 +278,122 @@ impl Comment {
         Ok(all_comments)
     }
 
+    pub async fn find_by_id(db: &Database, id: &str) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid comment ID"))?;
+        let comment = collection.find_one(doc! {"_id": object_id}).await?;
+
+        if comment.is_none() {
+            return Err(Error::custom("Comment not found"));
+        }
+
+        Ok(comment.unwrap())
+    }
+
+    /// Comments written by a registered user, including shadow banned ones
+    pub async fn find_by_author(db: &Database, author_id: ObjectId) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {"authorId": author_id})
+            .sort(doc! {"createdAt": -1})
+            .await?;
+        let mut all_comments: Vec<Self> = Vec::new();
+
+        while let Some(comment) = cursor.try_next().await? {
+            all_comments.push(comment);
+        }
+
+        Ok(all_comments)
+    }
+
+    pub async fn find_by_ip_hash(db: &Database, ip_hash: &str) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {"moderation.ipHash": ip_hash})
+            .sort(doc! {"createdAt": -1})
+            .await?;
+        let mut all_comments: Vec<Self> = Vec::new();
+
+        while let Some(comment) = cursor.try_next().await? {
+            all_comments.push(comment);
+        }
+
+        Ok(all_comments)
+    }
+
+    pub async fn get_by_tripcode(
+        db: &Database,
+        tripcode: &str,
+    ) -> Result<Vec<CommentResponse>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {"tripcode": tripcode, "shadowBanned": {"$ne": true}})
+            .sort(doc! {"createdAt": -1})
+            .await?;
+        let mut all_comments: Vec<CommentResponse> = Vec::new();
+
+        while let Some(comment) = cursor.try_next().await? {
+            all_comments.push(comment.to_response());
+        }
+
+        Ok(all_comments)
+    }
+
+    pub async fn update_body(db: &Database, id: &str, body: &str) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid comment ID"))?;
+        let result = collection
+            .update_one(
+                doc! {"_id": object_id},
+                doc! {"$set": {
+                    "body": body,
+                    "updatedAt": bson::DateTime::from_chrono(Utc::now()),
+                }},
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(Error::custom("Comment not found"));
+        }
+
+        Self::find_by_id(db, id).await
+    }
+
+    /// Digest identifying the comment for the duplicate check
+    ///
+    /// Signed in authors are told apart by their id, anonymous ones by the
+    /// name and email they used. Case and whitespace of the body are ignored.
+    pub fn digest(&self) -> String {
+        let author = match self.author_id {
+            Some(author_id) => author_id.to_hex(),
+            None => format!("{}\0{}", self.name, self.email),
+        };
+        let mut hasher = Sha256::new();
+
+        for field in [&author, &self.post_slug, &normalize_body(&self.body)] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+
+        HEXLOWER.encode(&hasher.finalize())
+    }
+
+    /// Remove moderation data from comments created before `before`
+    pub async fn purge_moderation(db: &Database, before: DateTime<Utc>) -> Result<u64, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let result = collection
+            .update_many(
+                doc! {
+                    "moderation": {"$exists": true},
+                    "createdAt": {"$lt": bson::DateTime::from_chrono(before)},
+                },
+                doc! {"$unset": {"moderation": ""}},
+            )
+            .await?;
+
+        Ok(result.modified_count)
+    }
+
     pub async fn delete(db: &Database, id: &str) -> Result<(), Error> {
         let collection = db.collection::<Self>(COLLECTION_NAME);
         let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid comment ID"))?;
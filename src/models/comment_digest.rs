@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{bson::doc, error::Error, options::IndexOptions, Database, IndexModel};
+use serde::{Deserialize, Serialize};
+
+use crate::database::is_duplicate_key_error;
+
+const COLLECTION_NAME: &str = "comment_digests";
+
+/// Comment recently posted by an author, keeping them from posting it again
+/// within the duplicate window
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommentDigest {
+    /// Digest of the author, post and normalized body
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl CommentDigest {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let index = IndexModel::builder()
+            .keys(doc! {"expiresAt": 1})
+            .options(
+                IndexOptions::builder()
+                    .expire_after(Duration::from_secs(0))
+                    .build(),
+            )
+            .build();
+
+        collection.create_index(index).await?;
+
+        Ok(())
+    }
+
+    /// Claim the digest until `expires_at`, failing if it's already claimed
+    ///
+    /// Expired digests the TTL monitor hasn't removed yet are claimed again,
+    /// and concurrent claims can't both succeed as the digest is the id.
+    pub async fn claim(db: &Database, id: &str, expires_at: DateTime<Utc>) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let result = collection
+            .update_one(
+                doc! {
+                    "_id": id,
+                    "expiresAt": {"$lte": bson::DateTime::from_chrono(Utc::now())},
+                },
+                doc! {"$set": {"expiresAt": bson::DateTime::from_chrono(expires_at)}},
+            )
+            .upsert(true)
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            Err(e) if is_duplicate_key_error(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Release a digest whose comment couldn't be saved
+    pub async fn release(db: &Database, id: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.delete_one(doc! {"_id": id}).await?;
+
+        Ok(())
+    }
+}
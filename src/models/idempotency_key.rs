@@ -0,0 +1,126 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{constants::time::ONE_DAY_IN_SECONDS, database::is_duplicate_key_error};
+
+const COLLECTION_NAME: &str = "idempotency_keys";
+
+/// Response recorded for an `Idempotency-Key`, kept for a day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IdempotencyKey {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// Key sent by the client, scoped to the client
+    pub key: String,
+
+    /// Hash of the request, to detect a key reused for another request
+    #[serde(rename = "requestHash")]
+    pub request_hash: String,
+
+    /// Status code of the recorded response, `None` while the request is in progress
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+
+    /// JSON body of the recorded response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+pub enum IdempotencyClaim {
+    /// First request with this key, the caller should process it
+    Acquired,
+    /// The key was already used
+    Existing(IdempotencyKey),
+}
+
+impl IdempotencyKey {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"key": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"createdAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(ONE_DAY_IN_SECONDS as u64))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Reserve the key for the current request
+    pub async fn claim(
+        db: &Database,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyClaim, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let record = Self {
+            id: None,
+            key: key.to_string(),
+            request_hash: request_hash.to_string(),
+            status: None,
+            body: None,
+            created_at: Utc::now(),
+        };
+
+        match collection.insert_one(record).await {
+            Ok(_) => Ok(IdempotencyClaim::Acquired),
+            Err(e) if is_duplicate_key_error(&e) => {
+                let existing = collection.find_one(doc! {"key": key}).await?;
+
+                existing
+                    .map(IdempotencyClaim::Existing)
+                    .ok_or_else(|| Error::custom("Idempotency key not found"))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Record the response of the request holding the key
+    pub async fn complete(db: &Database, key: &str, status: u16, body: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(
+                doc! {"key": key},
+                doc! {"$set": {"status": status as i32, "body": body}},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Free the key so the client can retry
+    pub async fn release(db: &Database, key: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.delete_one(doc! {"key": key}).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+const COLLECTION_NAME: &str = "identities";
+
+/// Account of a user at an external provider, used to sign in
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Identity {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// User the identity signs in as
+    #[serde(rename = "userId")]
+    pub user_id: ObjectId,
+
+    /// Name of the provider, as configured in `OAUTH_PROVIDERS`
+    pub provider: String,
+
+    /// Stable ID of the account at the provider
+    pub subject: String,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+impl Identity {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"provider": 1, "subject": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! {"userId": 1}).build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    pub async fn find(db: &Database, provider: &str, subject: &str) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .find_one(doc! {"provider": provider, "subject": subject})
+            .await
+    }
+
+    pub async fn link(
+        db: &Database,
+        user_id: ObjectId,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let identity = Self {
+            id: None,
+            user_id,
+            provider: provider.to_string(),
+            subject: subject.to_string(),
+            created_at: Utc::now(),
+        };
+
+        let result = collection.insert_one(identity.clone()).await?;
+
+        Ok(Self {
+            id: result.inserted_id.as_object_id(),
+            ..identity
+        })
+    }
+
+    /// Unlink every identity of a deleted user
+    pub async fn delete_by_user(db: &Database, user_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.delete_many(doc! {"userId": user_id}).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    auth::indieauth::AuthorizationServer,
+    constants::auth::OAUTH_STATE_LIFETIME_IN_SECONDS,
+    utils::encryption::{generate_token, hash_token},
+};
+
+const COLLECTION_NAME: &str = "indieauth_states";
+
+/// Pending IndieAuth authorization of a commenter's website
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndieAuthState {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// SHA-256 hash of the `state` parameter
+    #[serde(rename = "stateHash")]
+    pub state_hash: String,
+
+    /// Canonical profile URL entered by the commenter
+    pub me: String,
+
+    /// Authorization endpoint discovered from `me`
+    #[serde(rename = "authorizationEndpoint")]
+    pub authorization_endpoint: String,
+
+    /// Issuer identifier the callback's `iss` must match, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+
+    /// PKCE code verifier
+    #[serde(rename = "codeVerifier")]
+    pub code_verifier: String,
+
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IndieAuthState {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"stateHash": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"expiresAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Start an authorization and return its `state` parameter
+    pub async fn create(
+        db: &Database,
+        me: &str,
+        server: &AuthorizationServer,
+        code_verifier: &str,
+    ) -> Result<String, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let state = generate_token();
+
+        collection
+            .insert_one(Self {
+                id: None,
+                state_hash: hash_token(&state),
+                me: me.to_string(),
+                authorization_endpoint: server.authorization_endpoint.clone(),
+                issuer: server.issuer.clone(),
+                code_verifier: code_verifier.to_string(),
+                expires_at: Utc::now() + chrono::Duration::seconds(OAUTH_STATE_LIFETIME_IN_SECONDS),
+            })
+            .await?;
+
+        Ok(state)
+    }
+
+    /// Remove the authorization matching the `state` parameter and return it,
+    /// so a callback can only be handled once
+    pub async fn take(db: &Database, state: &str) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .find_one_and_delete(doc! {
+                "stateHash": hash_token(state),
+                "expiresAt": {"$gt": bson::DateTime::from_chrono(Utc::now())},
+            })
+            .await
+    }
+
+    /// Authorization server the user was sent to
+    pub fn server(&self) -> AuthorizationServer {
+        AuthorizationServer {
+            authorization_endpoint: self.authorization_endpoint.clone(),
+            issuer: self.issuer.clone(),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::encryption::{generate_token, hash_token};
+
+use super::user::UserRole;
+
+const COLLECTION_NAME: &str = "invites";
+
+/// Single-use code allowing someone to sign up with a given role
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Invite {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// SHA-256 hash of the code
+    #[serde(rename = "codeHash")]
+    pub code_hash: String,
+
+    /// Role given to the user signing up with the code
+    pub role: UserRole,
+
+    /// User who generated the code
+    #[serde(rename = "createdBy")]
+    pub created_by: ObjectId,
+
+    /// User who signed up with the code
+    #[serde(rename = "usedBy", default, skip_serializing_if = "Option::is_none")]
+    pub used_by: Option<ObjectId>,
+
+    #[serde(
+        rename = "usedAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub used_at: Option<DateTime<Utc>>,
+
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InviteResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    pub role: UserRole,
+
+    #[serde(rename = "usedBy")]
+    pub used_by: Option<String>,
+
+    #[serde(rename = "usedAt")]
+    pub used_at: Option<String>,
+
+    #[serde(rename = "expiresAt")]
+    pub expires_at: String,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl Invite {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![IndexModel::builder()
+            .keys(doc! {"codeHash": 1})
+            .options(IndexOptions::builder().unique(true).build())
+            .build()];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Create an invite and return it along with its code
+    ///
+    /// Only the hash of the code is stored, it can't be shown again.
+    pub async fn create(
+        db: &Database,
+        created_by: ObjectId,
+        role: UserRole,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(Self, String), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let code = generate_token();
+        let invite = Self {
+            id: None,
+            code_hash: hash_token(&code),
+            role,
+            created_by,
+            used_by: None,
+            used_at: None,
+            expires_at,
+            created_at: Utc::now(),
+        };
+
+        let result = collection.insert_one(invite.clone()).await?;
+        let invite = Self {
+            id: result.inserted_id.as_object_id(),
+            ..invite
+        };
+
+        Ok((invite, code))
+    }
+
+    pub async fn find_all(db: &Database) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {})
+            .sort(doc! {"createdAt": -1})
+            .await?;
+        let mut invites: Vec<Self> = Vec::new();
+
+        while let Some(invite) = cursor.try_next().await? {
+            invites.push(invite);
+        }
+
+        Ok(invites)
+    }
+
+    /// Mark the invite matching the code as used
+    ///
+    /// Returns `None` when the code is unknown, expired or already used.
+    pub async fn redeem(db: &Database, code: &str) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let now = bson::DateTime::from_chrono(Utc::now());
+
+        collection
+            .find_one_and_update(
+                doc! {
+                    "codeHash": hash_token(code),
+                    "usedAt": null,
+                    "expiresAt": {"$gt": now},
+                },
+                doc! {"$set": {"usedAt": now}},
+            )
+            .await
+    }
+
+    /// Record the user who signed up with the invite
+    pub async fn assign(db: &Database, id: ObjectId, user_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(doc! {"_id": id}, doc! {"$set": {"usedBy": user_id}})
+            .await?;
+
+        Ok(())
+    }
+
+    /// Make a redeemed invite usable again, when the signup failed
+    pub async fn release(db: &Database, id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(
+                doc! {"_id": id, "usedBy": null},
+                doc! {"$unset": {"usedAt": ""}},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete an invite that hasn't been used yet
+    pub async fn revoke(db: &Database, id: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid invite ID"))?;
+
+        let result = collection
+            .delete_one(doc! {"_id": object_id, "usedAt": null})
+            .await?;
+
+        if result.deleted_count == 0 {
+            return Err(Error::custom("Invite not found"));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_response(&self) -> InviteResponse {
+        InviteResponse {
+            id: self.id.unwrap().to_string(),
+            role: self.role.clone(),
+            used_by: self.used_by.map(|id| id.to_string()),
+            used_at: self.used_at.map(|date| date.to_rfc3339()),
+            expires_at: self.expires_at.to_rfc3339(),
+            created_at: self.created_at.to_rfc3339(),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::{IndexOptions, ReturnDocument},
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::auth::{
+    LOGIN_ATTEMPT_RETENTION_IN_SECONDS, LOGIN_LOCKOUT_BASE_IN_SECONDS, LOGIN_LOCKOUT_MAX_IN_SECONDS,
+};
+
+const COLLECTION_NAME: &str = "login_attempts";
+
+/// Failed signins of an account name or an IP address, or magic links
+/// requested for an address
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoginAttempt {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// `name:<name>`, `ip:<ip hash>`, `email:<email>`,
+    /// `magic-link-ip:<ip hash>`, `tripcode-ip:<ip hash>`,
+    /// `tripcode:<comment id>:<ip hash>` or `password-change:<user id>`
+    pub key: String,
+
+    /// Failures since the last successful signin
+    pub failures: i32,
+
+    /// Signins are refused until then
+    #[serde(
+        rename = "lockedUntil",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub locked_until: Option<DateTime<Utc>>,
+
+    #[serde(
+        rename = "lastFailureAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub last_failure_at: DateTime<Utc>,
+}
+
+impl LoginAttempt {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"key": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"lastFailureAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(
+                            LOGIN_ATTEMPT_RETENTION_IN_SECONDS as u64,
+                        ))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    pub fn name_key(name: &str) -> String {
+        format!("name:{}", name.to_lowercase())
+    }
+
+    pub fn ip_key(ip_hash: &str) -> String {
+        format!("ip:{}", ip_hash)
+    }
+
+    pub fn email_key(email: &str) -> String {
+        format!("email:{}", email.to_lowercase())
+    }
+
+    /// Magic link requests are counted apart from failed signins of the IP
+    /// address, so they can't lock the password signin
+    pub fn magic_link_ip_key(ip_hash: &str) -> String {
+        format!("magic-link-ip:{}", ip_hash)
+    }
+
+    /// Tripcode secrets guessed from an IP address are counted apart from
+    /// failed signins, so they can't lock the password signin
+    pub fn tripcode_ip_key(ip_hash: &str) -> String {
+        format!("tripcode-ip:{}", ip_hash)
+    }
+
+    /// Tripcode secrets guessed for a comment from an IP address, so others
+    /// can't lock the author out of editing it
+    pub fn tripcode_key(comment_id: &str, ip_hash: &str) -> String {
+        format!("tripcode:{}:{}", comment_id, ip_hash)
+    }
+
+    /// Current passwords guessed from a session of the user, apart from
+    /// signins so a stolen session can't lock the account
+    pub fn password_change_key(user_id: &ObjectId) -> String {
+        format!("password-change:{}", user_id)
+    }
+
+    /// Latest time any of the keys is locked until, if one is locked
+    pub async fn locked_until(
+        db: &Database,
+        keys: &[String],
+    ) -> Result<Option<DateTime<Utc>>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {
+                "key": {"$in": keys},
+                "lockedUntil": {"$gt": bson::DateTime::from_chrono(Utc::now())},
+            })
+            .await?;
+        let mut locked_until: Option<DateTime<Utc>> = None;
+
+        while let Some(attempt) = cursor.try_next().await? {
+            locked_until = locked_until.max(attempt.locked_until);
+        }
+
+        Ok(locked_until)
+    }
+
+    /// Count a failed signin, locking the key once it failed `max_failures` times
+    ///
+    /// Returns when the key is locked until, if this failure locked it.
+    pub async fn record_failure(
+        db: &Database,
+        key: &str,
+        max_failures: i32,
+    ) -> Result<Option<DateTime<Utc>>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let attempt = collection
+            .find_one_and_update(
+                doc! {"key": key},
+                doc! {
+                    "$inc": {"failures": 1},
+                    "$set": {"lastFailureAt": bson::DateTime::from_chrono(Utc::now())},
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?;
+
+        let lockout =
+            match attempt.and_then(|attempt| lockout_seconds(attempt.failures, max_failures)) {
+                Some(lockout) => lockout,
+                None => return Ok(None),
+            };
+        let locked_until = Utc::now() + chrono::Duration::seconds(lockout);
+
+        collection
+            .update_one(
+                doc! {"key": key},
+                doc! {"$set": {"lockedUntil": bson::DateTime::from_chrono(locked_until)}},
+            )
+            .await?;
+
+        Ok(Some(locked_until))
+    }
+
+    /// Forget the failures of the keys after a successful signin
+    pub async fn clear(db: &Database, keys: &[String]) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.delete_many(doc! {"key": {"$in": keys}}).await?;
+
+        Ok(())
+    }
+}
+
+/// How long to lock a key after `failures` failures, doubling from
+/// `LOGIN_LOCKOUT_BASE_IN_SECONDS` for every failure past `max_failures`
+pub fn lockout_seconds(failures: i32, max_failures: i32) -> Option<i64> {
+    if failures < max_failures {
+        return None;
+    }
+
+    let doublings = (failures - max_failures).min(32) as u32;
+
+    Some(
+        LOGIN_LOCKOUT_BASE_IN_SECONDS
+            .saturating_mul(2_i64.saturating_pow(doublings))
+            .min(LOGIN_LOCKOUT_MAX_IN_SECONDS),
+    )
+}
@@ -1,2 +1,21 @@
+mod __tests__;
+
+pub mod api_token;
+pub mod audit_log;
+pub mod ban;
+pub mod bootstrap;
 pub mod comment;
+pub mod comment_digest;
+pub mod idempotency_key;
+pub mod identity;
+pub mod indieauth_state;
+pub mod invite;
+pub mod login_attempt;
+pub mod oauth_state;
+pub mod passkey_credential;
+pub mod password_reset_token;
+pub mod refresh_token;
+pub mod revoked_token;
+pub mod session;
 pub mod user;
+pub mod webauthn_challenge;
@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::auth::OAUTH_STATE_LIFETIME_IN_SECONDS,
+    utils::encryption::{generate_token, hash_token},
+};
+
+const COLLECTION_NAME: &str = "oauth_states";
+
+/// Pending authorization at an OAuth provider
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OAuthState {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// SHA-256 hash of the `state` parameter
+    #[serde(rename = "stateHash")]
+    pub state_hash: String,
+
+    /// Name of the provider the user was sent to
+    pub provider: String,
+
+    /// PKCE code verifier
+    #[serde(rename = "codeVerifier")]
+    pub code_verifier: String,
+
+    /// OIDC nonce the ID token must carry
+    pub nonce: String,
+
+    /// Signed in user linking the identity to their account
+    #[serde(
+        rename = "linkUserId",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub link_user_id: Option<ObjectId>,
+
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl OAuthState {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"stateHash": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"expiresAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Start an authorization and return its `state` parameter
+    pub async fn create(
+        db: &Database,
+        provider: &str,
+        code_verifier: &str,
+        nonce: &str,
+        link_user_id: Option<ObjectId>,
+    ) -> Result<String, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let state = generate_token();
+
+        collection
+            .insert_one(Self {
+                id: None,
+                state_hash: hash_token(&state),
+                provider: provider.to_string(),
+                code_verifier: code_verifier.to_string(),
+                nonce: nonce.to_string(),
+                link_user_id,
+                expires_at: Utc::now() + chrono::Duration::seconds(OAUTH_STATE_LIFETIME_IN_SECONDS),
+            })
+            .await?;
+
+        Ok(state)
+    }
+
+    /// Remove the authorization matching the `state` parameter and return it,
+    /// so a callback can only be handled once
+    pub async fn take(db: &Database, provider: &str, state: &str) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .find_one_and_delete(doc! {
+                "stateHash": hash_token(state),
+                "provider": provider,
+                "expiresAt": {"$gt": bson::DateTime::from_chrono(Utc::now())},
+            })
+            .await
+    }
+}
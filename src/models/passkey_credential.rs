@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use webauthn_rs::prelude::Passkey;
+
+const COLLECTION_NAME: &str = "passkeys";
+
+/// Passkey a user registered to sign in without a password
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasskeyCredential {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// Owner of the passkey
+    #[serde(rename = "userId")]
+    pub user_id: ObjectId,
+
+    /// Name to recognize the passkey by
+    pub name: String,
+
+    /// Base64url encoded credential ID, to find the passkey used to sign in
+    #[serde(rename = "credentialId")]
+    pub credential_id: String,
+
+    /// Public key and signature counter of the credential
+    pub passkey: Passkey,
+
+    #[serde(
+        rename = "lastUsedAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub last_used_at: Option<DateTime<Utc>>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasskeyCredentialResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    pub name: String,
+
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<String>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl PasskeyCredential {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"credentialId": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! {"userId": 1}).build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    pub async fn create(db: &Database, passkey_credential: Self) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let result = collection.insert_one(passkey_credential.clone()).await?;
+
+        Ok(Self {
+            id: result.inserted_id.as_object_id(),
+            ..passkey_credential
+        })
+    }
+
+    pub async fn find_by_user(db: &Database, user_id: ObjectId) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {"userId": user_id})
+            .sort(doc! {"createdAt": -1})
+            .await?;
+        let mut passkey_credentials: Vec<Self> = Vec::new();
+
+        while let Some(passkey_credential) = cursor.try_next().await? {
+            passkey_credentials.push(passkey_credential);
+        }
+
+        Ok(passkey_credentials)
+    }
+
+    pub async fn find_by_credential_id(
+        db: &Database,
+        credential_id: &str,
+    ) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .find_one(doc! {"credentialId": credential_id})
+            .await
+    }
+
+    /// Store the updated signature counter after a signin
+    pub async fn record_use(db: &Database, id: ObjectId, passkey: &Passkey) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let passkey = bson::to_bson(passkey)?;
+
+        collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {"$set": {
+                    "passkey": passkey,
+                    "lastUsedAt": bson::DateTime::from_chrono(Utc::now()),
+                }},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delete a passkey of the user
+    pub async fn delete(db: &Database, user_id: ObjectId, id: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = ObjectId::parse_str(id).map_err(|_| Error::custom("Invalid passkey ID"))?;
+
+        let result = collection
+            .delete_one(doc! {"_id": object_id, "userId": user_id})
+            .await?;
+
+        if result.deleted_count == 0 {
+            return Err(Error::custom("Passkey not found"));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_response(&self) -> PasskeyCredentialResponse {
+        PasskeyCredentialResponse {
+            id: self.id.unwrap().to_string(),
+            name: self.name.clone(),
+            last_used_at: self.last_used_at.map(|date| date.to_rfc3339()),
+            created_at: self.created_at.to_rfc3339(),
+        }
+    }
+
+    /// Remove the passkeys of a deleted user
+    pub async fn delete_by_user(db: &Database, user_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.delete_many(doc! {"userId": user_id}).await?;
+
+        Ok(())
+    }
+}
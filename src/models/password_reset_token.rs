@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::auth::PASSWORD_RESET_TOKEN_LIFETIME_IN_SECONDS,
+    utils::encryption::{generate_token, hash_token},
+};
+
+const COLLECTION_NAME: &str = "password_reset_tokens";
+
+/// Single-use token allowing to set a new password without the current one
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasswordResetToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// User whose password can be reset
+    #[serde(rename = "userId")]
+    pub user_id: ObjectId,
+
+    /// SHA-256 hash of the token
+    #[serde(rename = "tokenHash")]
+    pub token_hash: String,
+
+    #[serde(
+        rename = "usedAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub used_at: Option<DateTime<Utc>>,
+
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasswordResetToken {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"tokenHash": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! {"userId": 1}).build(),
+            IndexModel::builder()
+                .keys(doc! {"expiresAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Issue a reset token for the user and return it
+    ///
+    /// Tokens issued to the user before stop working.
+    pub async fn issue(db: &Database, user_id: ObjectId) -> Result<String, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let token = generate_token();
+
+        collection
+            .delete_many(doc! {"userId": user_id, "usedAt": null})
+            .await?;
+        collection
+            .insert_one(Self {
+                id: None,
+                user_id,
+                token_hash: hash_token(&token),
+                used_at: None,
+                expires_at: Utc::now()
+                    + chrono::Duration::seconds(PASSWORD_RESET_TOKEN_LIFETIME_IN_SECONDS),
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(token)
+    }
+
+    /// Find the token if it can still be used, without consuming it
+    pub async fn find_active(db: &Database, token: &str) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .find_one(doc! {
+                "tokenHash": hash_token(token),
+                "usedAt": null,
+                "expiresAt": {"$gt": bson::DateTime::from_chrono(Utc::now())},
+            })
+            .await
+    }
+
+    /// Mark the token as used and return its user
+    ///
+    /// Returns `None` when the token is unknown, expired or already used.
+    pub async fn consume(db: &Database, token: &str) -> Result<Option<ObjectId>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let now = bson::DateTime::from_chrono(Utc::now());
+        let reset_token = collection
+            .find_one_and_update(
+                doc! {
+                    "tokenHash": hash_token(token),
+                    "usedAt": null,
+                    "expiresAt": {"$gt": now},
+                },
+                doc! {"$set": {"usedAt": now}},
+            )
+            .await?;
+
+        Ok(reset_token.map(|reset_token| reset_token.user_id))
+    }
+}
@@ -0,0 +1,225 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::auth::REFRESH_TOKEN_LIFETIME_IN_SECONDS,
+    utils::encryption::{generate_token, hash_token},
+};
+
+const COLLECTION_NAME: &str = "refresh_tokens";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// User the token was issued to
+    #[serde(rename = "userId")]
+    pub user_id: ObjectId,
+
+    /// Tokens rotated from the same sign in share a family
+    #[serde(rename = "familyId")]
+    pub family_id: ObjectId,
+
+    /// SHA-256 hash of the token
+    #[serde(rename = "tokenHash")]
+    pub token_hash: String,
+
+    /// When the token was exchanged for a new one
+    #[serde(
+        rename = "usedAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub used_at: Option<DateTime<Utc>>,
+
+    /// When the token family was revoked
+    #[serde(
+        rename = "revokedAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub revoked_at: Option<DateTime<Utc>>,
+
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+pub enum RefreshResult {
+    /// The token was rotated, holds the owner, the family and the new token
+    Rotated(ObjectId, ObjectId, String),
+    /// The token was already rotated, the whole family has been revoked
+    Reused,
+    /// The token is unknown, expired or revoked
+    Invalid,
+}
+
+impl RefreshToken {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"tokenHash": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder().keys(doc! {"familyId": 1}).build(),
+            IndexModel::builder().keys(doc! {"userId": 1}).build(),
+            IndexModel::builder()
+                .keys(doc! {"expiresAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Issue a new refresh token and return it
+    ///
+    /// Only the hash is stored, the token itself can't be recovered later.
+    pub async fn issue(
+        db: &Database,
+        user_id: ObjectId,
+        family_id: ObjectId,
+    ) -> Result<String, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let token = generate_token();
+        let refresh_token = Self {
+            id: None,
+            user_id,
+            family_id,
+            token_hash: hash_token(&token),
+            used_at: None,
+            revoked_at: None,
+            expires_at: Utc::now() + chrono::Duration::seconds(REFRESH_TOKEN_LIFETIME_IN_SECONDS),
+            created_at: Utc::now(),
+        };
+
+        collection.insert_one(refresh_token).await?;
+
+        Ok(token)
+    }
+
+    /// Exchange a refresh token for a new one of the same family
+    ///
+    /// A token can be exchanged only once. Presenting it again means it
+    /// leaked, so every token of its family is revoked.
+    pub async fn rotate(db: &Database, token: &str) -> Result<RefreshResult, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let token_hash = hash_token(token);
+        let now = bson::DateTime::from_chrono(Utc::now());
+
+        let refresh_token = collection
+            .find_one_and_update(
+                doc! {
+                    "tokenHash": &token_hash,
+                    "usedAt": null,
+                    "revokedAt": null,
+                    "expiresAt": {"$gt": now},
+                },
+                doc! {"$set": {"usedAt": now}},
+            )
+            .await?;
+
+        if let Some(refresh_token) = refresh_token {
+            let token = Self::issue(db, refresh_token.user_id, refresh_token.family_id).await?;
+
+            return Ok(RefreshResult::Rotated(
+                refresh_token.user_id,
+                refresh_token.family_id,
+                token,
+            ));
+        }
+
+        let refresh_token = collection.find_one(doc! {"tokenHash": &token_hash}).await?;
+
+        match refresh_token {
+            Some(refresh_token) if refresh_token.used_at.is_some() => {
+                log::warn!(
+                    "[RefreshToken] Reuse detected, revoking family {}",
+                    refresh_token.family_id
+                );
+                Self::revoke_family(db, refresh_token.family_id).await?;
+
+                Ok(RefreshResult::Reused)
+            }
+            _ => Ok(RefreshResult::Invalid),
+        }
+    }
+
+    /// Family of the given token, which is the session it belongs to
+    pub async fn find_family(db: &Database, token: &str) -> Result<Option<ObjectId>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let refresh_token = collection
+            .find_one(doc! {"tokenHash": hash_token(token)})
+            .await?;
+
+        Ok(refresh_token.map(|refresh_token| refresh_token.family_id))
+    }
+
+    /// Revoke the family of the given token
+    pub async fn revoke_by_token(db: &Database, token: &str) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let refresh_token = collection
+            .find_one(doc! {"tokenHash": hash_token(token)})
+            .await?;
+
+        if let Some(refresh_token) = refresh_token {
+            Self::revoke_family(db, refresh_token.family_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Revoke every token of the user, signing them out everywhere
+    pub async fn revoke_user(db: &Database, user_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_many(
+                doc! {"userId": user_id, "revokedAt": null},
+                doc! {"$set": {"revokedAt": bson::DateTime::from_chrono(Utc::now())}},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke_family(db: &Database, family_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_many(
+                doc! {"familyId": family_id, "revokedAt": null},
+                doc! {"$set": {"revokedAt": bson::DateTime::from_chrono(Utc::now())}},
+            )
+            .await?;
+
+        Ok(())
+    }
+}
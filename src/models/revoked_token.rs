@@ -0,0 +1,100 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::database::is_duplicate_key_error;
+
+const COLLECTION_NAME: &str = "revoked_tokens";
+
+/// Access token revoked before it expired
+///
+/// Entries are removed by MongoDB once the token would have expired anyway.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevokedToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// `jti` claim of the revoked token
+    pub jti: String,
+
+    /// When the revoked token expires
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl RevokedToken {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"jti": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"expiresAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke(db: &Database, jti: &str, expires_at: DateTime<Utc>) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let revoked_token = Self {
+            id: None,
+            jti: jti.to_string(),
+            expires_at,
+        };
+
+        match collection.insert_one(revoked_token).await {
+            Ok(_) => Ok(()),
+            // Already revoked
+            Err(e) if is_duplicate_key_error(&e) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Revoke a single-use token, failing if it was already used
+    pub async fn revoke_once(
+        db: &Database,
+        jti: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let revoked_token = Self {
+            id: None,
+            jti: jti.to_string(),
+            expires_at,
+        };
+
+        match collection.insert_one(revoked_token).await {
+            Ok(_) => Ok(true),
+            Err(e) if is_duplicate_key_error(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn is_revoked(db: &Database, jti: &str) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let revoked_token = collection.find_one(doc! {"jti": jti}).await?;
+
+        Ok(revoked_token.is_some())
+    }
+}
@@ -0,0 +1,270 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::auth::REFRESH_TOKEN_LIFETIME_IN_SECONDS, database::is_duplicate_key_error,
+    utils::user_agent,
+};
+
+const COLLECTION_NAME: &str = "sessions";
+
+/// Signed in device of a user
+///
+/// Its id is the family of its refresh tokens, and the `sid` claim of its
+/// access tokens.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Session {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    #[serde(rename = "userId")]
+    pub user_id: ObjectId,
+
+    /// Description of the device, such as `Firefox on Windows`
+    pub device: String,
+
+    #[serde(rename = "userAgent", skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// Salted hash of the IP address the session was last seen from
+    #[serde(rename = "ipHash", skip_serializing_if = "Option::is_none")]
+    pub ip_hash: Option<String>,
+
+    #[serde(
+        rename = "revokedAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub revoked_at: Option<DateTime<Utc>>,
+
+    /// Extended whenever the session is refreshed
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+
+    #[serde(
+        rename = "lastSeenAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub last_seen_at: DateTime<Utc>,
+
+    /// Creation timestamp, automatically managed
+    #[serde(
+        rename = "createdAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    pub device: String,
+
+    #[serde(rename = "userAgent")]
+    pub user_agent: Option<String>,
+
+    /// Whether this is the session the request was made with
+    pub current: bool,
+
+    #[serde(rename = "lastSeenAt")]
+    pub last_seen_at: String,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+impl Session {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder().keys(doc! {"userId": 1}).build(),
+            IndexModel::builder()
+                .keys(doc! {"expiresAt": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(Duration::from_secs(0))
+                        .build(),
+                )
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    pub async fn create(
+        db: &Database,
+        user_id: ObjectId,
+        user_agent: Option<String>,
+        ip_hash: Option<String>,
+    ) -> Result<Self, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let session = Self {
+            id: None,
+            user_id,
+            device: describe(user_agent.as_deref()),
+            user_agent,
+            ip_hash,
+            revoked_at: None,
+            expires_at: Utc::now() + chrono::Duration::seconds(REFRESH_TOKEN_LIFETIME_IN_SECONDS),
+            last_seen_at: Utc::now(),
+            created_at: Utc::now(),
+        };
+
+        let result = collection.insert_one(session.clone()).await?;
+
+        Ok(Self {
+            id: result.inserted_id.as_object_id(),
+            ..session
+        })
+    }
+
+    /// Extend a session when its refresh token is rotated
+    ///
+    /// Sessions signed in before they were recorded are created on the fly.
+    /// Returns `false` if the session was revoked.
+    pub async fn resume(
+        db: &Database,
+        id: ObjectId,
+        user_id: ObjectId,
+        user_agent: Option<String>,
+        ip_hash: Option<String>,
+    ) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let now = Utc::now();
+        let expires_at = now + chrono::Duration::seconds(REFRESH_TOKEN_LIFETIME_IN_SECONDS);
+        let result = collection
+            .update_one(
+                doc! {"_id": id, "userId": user_id, "revokedAt": null},
+                doc! {
+                    "$set": {
+                        "ipHash": ip_hash,
+                        "expiresAt": bson::DateTime::from_chrono(expires_at),
+                        "lastSeenAt": bson::DateTime::from_chrono(now),
+                    },
+                    "$setOnInsert": {
+                        "device": describe(user_agent.as_deref()),
+                        "userAgent": user_agent,
+                        "createdAt": bson::DateTime::from_chrono(now),
+                    },
+                },
+            )
+            .upsert(true)
+            .await;
+
+        match result {
+            Ok(_) => Ok(true),
+            // The session exists but didn't match, so it was revoked
+            Err(e) if is_duplicate_key_error(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn find_active(db: &Database, id: ObjectId) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .find_one(doc! {
+                "_id": id,
+                "revokedAt": null,
+                "expiresAt": {"$gt": bson::DateTime::from_chrono(Utc::now())},
+            })
+            .await
+    }
+
+    pub async fn find_active_by_user(db: &Database, user_id: ObjectId) -> Result<Vec<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let mut cursor = collection
+            .find(doc! {
+                "userId": user_id,
+                "revokedAt": null,
+                "expiresAt": {"$gt": bson::DateTime::from_chrono(Utc::now())},
+            })
+            .sort(doc! {"lastSeenAt": -1})
+            .await?;
+        let mut sessions: Vec<Self> = Vec::new();
+
+        while let Some(session) = cursor.try_next().await? {
+            sessions.push(session);
+        }
+
+        Ok(sessions)
+    }
+
+    /// Record activity of the session
+    pub async fn touch(db: &Database, id: ObjectId, ip_hash: Option<String>) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {"$set": {
+                    "ipHash": ip_hash,
+                    "lastSeenAt": bson::DateTime::from_chrono(Utc::now()),
+                }},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Revoke a session of the user, returning whether it was active
+    pub async fn revoke(db: &Database, user_id: ObjectId, id: ObjectId) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let result = collection
+            .update_one(
+                doc! {"_id": id, "userId": user_id, "revokedAt": null},
+                doc! {"$set": {"revokedAt": bson::DateTime::from_chrono(Utc::now())}},
+            )
+            .await?;
+
+        Ok(result.modified_count == 1)
+    }
+
+    /// Revoke every session of the user
+    pub async fn revoke_user(db: &Database, user_id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_many(
+                doc! {"userId": user_id, "revokedAt": null},
+                doc! {"$set": {"revokedAt": bson::DateTime::from_chrono(Utc::now())}},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub fn to_response(&self, current: Option<ObjectId>) -> SessionResponse {
+        SessionResponse {
+            id: self.id.unwrap().to_hex(),
+            device: self.device.clone(),
+            user_agent: self.user_agent.clone(),
+            current: self.id.is_some() && self.id == current,
+            last_seen_at: self.last_seen_at.to_rfc3339(),
+            created_at: self.created_at.to_rfc3339(),
+        }
+    }
+}
+
+fn describe(user_agent: Option<&str>) -> String {
+    user_agent
+        .map(user_agent::describe)
+        .unwrap_or_else(|| "Unknown device".to_string())
+}
@@ -1,19 +1,36 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    sync::{Arc, RwLock},
+};
 
 use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
 use mongodb::{
-    bson::{doc, oid::ObjectId},
+    bson::{doc, oid::ObjectId, Document},
     error::Error,
-    Database,
+    options::{Collation, CollationStrength, IndexOptions},
+    Database, IndexModel,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::utils::{confusable::is_confusable, pattern::escape_regex};
+
 const COLLECTION_NAME: &str = "users";
 
+/// Names differing only by case are considered the same
+fn name_collation() -> Collation {
+    Collation::builder()
+        .locale("en")
+        .strength(CollationStrength::Secondary)
+        .build()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum UserRole {
     /// Root user of entire application
     Root,
+    /// Users moderating comments, see `Permission` for what they can do
+    Moderator,
     /// Other all users
     User,
 }
@@ -22,11 +39,36 @@ impl Display for UserRole {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             UserRole::Root => write!(f, "Root"),
+            UserRole::Moderator => write!(f, "Moderator"),
             UserRole::User => write!(f, "User"),
         }
     }
 }
 
+/// TOTP two-factor authentication of a user
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserTotp {
+    /// Base32 encoded secret shared with the authenticator
+    pub secret: String,
+
+    /// When enrollment was confirmed, `None` while it's pending
+    #[serde(
+        rename = "enabledAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub enabled_at: Option<DateTime<Utc>>,
+
+    /// Time step of the last code used, older codes are rejected
+    #[serde(rename = "lastUsedStep", default)]
+    pub last_used_step: i64,
+
+    /// SHA-256 hashes of the unused recovery codes
+    #[serde(rename = "recoveryCodes", default)]
+    pub recovery_codes: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
     /// User id
@@ -34,14 +76,37 @@ pub struct User {
     pub id: Option<ObjectId>,
     /// User name
     pub name: String,
+    /// Lowercased email address, used to sign in with a magic link
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
     #[serde(skip_serializing)]
     /// User password
     pub password: String,
     /// Role of the user
     ///
     /// - `Root`: Root user of entire application
+    /// - `Moderator`: Users moderating comments
     /// - `User`: Other all users
     pub role: UserRole,
+    /// Tokens issued before this are rejected, set when the password changes
+    #[serde(
+        rename = "sessionsValidAfter",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub sessions_valid_after: Option<DateTime<Utc>>,
+    /// Two-factor authentication, managed through the `*_totp` methods
+    #[serde(default, skip_serializing)]
+    pub totp: Option<UserTotp>,
+    /// When the account was disabled, disabled users can't sign in
+    #[serde(
+        rename = "disabledAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime_optional"
+    )]
+    pub disabled_at: Option<DateTime<Utc>>,
     /// Creation timestamp, automatically managed
     #[serde(
         rename = "createdAt",
@@ -56,11 +121,67 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
+/// User as seen by administrators
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+
+    pub role: UserRole,
+
+    #[serde(rename = "totpEnabled")]
+    pub totp_enabled: bool,
+
+    #[serde(rename = "disabledAt")]
+    pub disabled_at: Option<String>,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
 impl User {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![
+            IndexModel::builder()
+                .keys(doc! {"name": 1})
+                .options(
+                    IndexOptions::builder()
+                        .unique(true)
+                        .collation(name_collation())
+                        .build(),
+                )
+                .build(),
+            IndexModel::builder()
+                .keys(doc! {"email": 1})
+                .options(IndexOptions::builder().unique(true).sparse(true).build())
+                .build(),
+        ];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
     pub async fn create(db: &Database, user: Self) -> Result<Self, Error> {
-        let collection = db.collection(COLLECTION_NAME);
-        let result = collection.insert_one(user.clone()).await?;
-        let user = collection
+        // `password` is skipped when serializing, so it doesn't leak into responses
+        let mut document = bson::to_document(&user)?;
+        document.insert("password", &user.password);
+
+        let result = db
+            .collection::<Document>(COLLECTION_NAME)
+            .insert_one(document)
+            .await?;
+        let user = db
+            .collection::<Self>(COLLECTION_NAME)
             .find_one(doc! {"_id": result.inserted_id})
             .await?;
 
@@ -86,9 +207,21 @@ impl User {
         Ok(user.unwrap())
     }
 
+    /// User with the id, `None` when there is none
+    pub async fn find_by_object_id(db: &Database, id: ObjectId) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.find_one(doc! {"_id": id}).await
+    }
+
     pub async fn find_by_name(db: &Database, name: &str) -> Result<Self, Error> {
         let collection = db.collection(COLLECTION_NAME);
-        let user = collection.find_one(doc! {"name": name}).await?;
+        // Same collation as the unique index, so the index is used and names
+        // are matched regardless of case
+        let user = collection
+            .find_one(doc! {"name": name})
+            .collation(name_collation())
+            .await?;
 
         if user.is_none() {
             return Err(Error::custom("User not found"));
@@ -96,4 +229,282 @@ impl User {
 
         Ok(user.unwrap())
     }
+
+    pub async fn find_by_email(db: &Database, email: &str) -> Result<Option<Self>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .find_one(doc! {"email": email.to_lowercase()})
+            .await
+    }
+
+    /// Users matching `search` in their name or email, newest first, with
+    /// the total count of matching users
+    pub async fn find_page(
+        db: &Database,
+        search: Option<&str>,
+        skip: u64,
+        limit: i64,
+    ) -> Result<(Vec<Self>, u64), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let filter = match search {
+            Some(search) => {
+                let pattern = escape_regex(search);
+                doc! {"$or": [
+                    {"name": {"$regex": &pattern, "$options": "i"}},
+                    {"email": {"$regex": &pattern, "$options": "i"}},
+                ]}
+            }
+            None => doc! {},
+        };
+        let total = collection.count_documents(filter.clone()).await?;
+        let mut cursor = collection
+            .find(filter)
+            .sort(doc! {"createdAt": -1})
+            .skip(skip)
+            .limit(limit)
+            .await?;
+        let mut users: Vec<Self> = Vec::new();
+
+        while let Some(user) = cursor.try_next().await? {
+            users.push(user);
+        }
+
+        Ok((users, total))
+    }
+
+    /// Whether no user has been created yet
+    pub async fn is_empty(db: &Database) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let user = collection.find_one(doc! {}).await?;
+
+        Ok(user.is_none())
+    }
+
+    /// Set a new password hash and invalidate the access tokens issued so far
+    pub async fn update_password(
+        db: &Database,
+        id: ObjectId,
+        hashed_password: &str,
+    ) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        // Token timestamps are in seconds
+        let now = bson::DateTime::from_millis(Utc::now().timestamp() * 1000);
+
+        collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {"$set": {
+                    "password": hashed_password,
+                    "sessionsValidAfter": now,
+                    "updatedAt": now,
+                }},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Whether a token issued at `issued_at` (a Unix timestamp) is still valid
+    pub fn accepts_token_issued_at(&self, issued_at: i64) -> bool {
+        self.sessions_valid_after
+            .is_none_or(|valid_after| issued_at >= valid_after.timestamp())
+    }
+
+    /// Whether signing in requires a TOTP code
+    pub fn is_totp_enabled(&self) -> bool {
+        self.totp
+            .as_ref()
+            .is_some_and(|totp| totp.enabled_at.is_some())
+    }
+
+    /// Start TOTP enrollment with a new secret, unless it's already enabled
+    pub async fn start_totp(db: &Database, id: ObjectId, secret: &str) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let result = collection
+            .update_one(
+                doc! {"_id": id, "totp.enabledAt": null},
+                doc! {"$set": {"totp": {
+                    "secret": secret,
+                    "lastUsedStep": 0_i64,
+                    "recoveryCodes": [],
+                }}},
+            )
+            .await?;
+
+        Ok(result.matched_count == 1)
+    }
+
+    /// Confirm TOTP enrollment with the step of the first valid code
+    pub async fn enable_totp(
+        db: &Database,
+        id: ObjectId,
+        step: i64,
+        recovery_code_hashes: Vec<String>,
+    ) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(
+                doc! {"_id": id, "totp.enabledAt": null},
+                doc! {"$set": {
+                    "totp.enabledAt": bson::DateTime::from_chrono(Utc::now()),
+                    "totp.lastUsedStep": step,
+                    "totp.recoveryCodes": recovery_code_hashes,
+                }},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn disable_totp(db: &Database, id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(doc! {"_id": id}, doc! {"$unset": {"totp": ""}})
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record the use of a TOTP code, failing if a code of the step or a
+    /// later one was already used
+    pub async fn use_totp_step(db: &Database, id: ObjectId, step: i64) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let result = collection
+            .update_one(
+                doc! {"_id": id, "totp.lastUsedStep": {"$lt": step}},
+                doc! {"$set": {"totp.lastUsedStep": step}},
+            )
+            .await?;
+
+        Ok(result.modified_count == 1)
+    }
+
+    /// Spend a recovery code, failing if it's unknown or already used
+    pub async fn use_recovery_code(
+        db: &Database,
+        id: ObjectId,
+        code_hash: &str,
+    ) -> Result<bool, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let result = collection
+            .update_one(
+                doc! {"_id": id, "totp.recoveryCodes": code_hash},
+                doc! {"$pull": {"totp.recoveryCodes": code_hash}},
+            )
+            .await?;
+
+        Ok(result.modified_count == 1)
+    }
+
+    pub async fn update_role(db: &Database, id: ObjectId, role: &UserRole) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {"$set": {
+                    "role": role.to_string(),
+                    "updatedAt": bson::DateTime::from_chrono(Utc::now()),
+                }},
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Disable or re-enable the account
+    pub async fn set_disabled(db: &Database, id: ObjectId, disabled: bool) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let now = bson::DateTime::from_chrono(Utc::now());
+        let update = if disabled {
+            doc! {"$set": {"disabledAt": now, "updatedAt": now}}
+        } else {
+            doc! {"$unset": {"disabledAt": ""}, "$set": {"updatedAt": now}}
+        };
+
+        collection.update_one(doc! {"_id": id}, update).await?;
+
+        Ok(())
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled_at.is_some()
+    }
+
+    pub async fn delete(db: &Database, id: ObjectId) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+
+        collection.delete_one(doc! {"_id": id}).await?;
+
+        Ok(())
+    }
+
+    pub fn to_response(&self) -> UserResponse {
+        UserResponse {
+            id: self.id.unwrap().to_hex(),
+            name: self.name.clone(),
+            email: self.email.clone(),
+            role: self.role.clone(),
+            totp_enabled: self.is_totp_enabled(),
+            disabled_at: self.disabled_at.map(|disabled_at| disabled_at.to_rfc3339()),
+            created_at: self.created_at.to_rfc3339(),
+            updated_at: self.updated_at.to_rfc3339(),
+        }
+    }
+
+    pub async fn find_all_names(db: &Database) -> Result<Vec<String>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let names = collection.distinct("name", doc! {}).await?;
+
+        Ok(names
+            .into_iter()
+            .filter_map(|name| name.as_str().map(str::to_string))
+            .collect())
+    }
+}
+
+/// In-memory copy of the names of the users, shared between requests
+#[derive(Debug, Clone, Default)]
+pub struct UserNameCache {
+    names: Arc<RwLock<Vec<String>>>,
+}
+
+impl UserNameCache {
+    pub async fn reload(&self, db: &Database) -> Result<(), Error> {
+        let names = User::find_all_names(db).await?;
+
+        *self.names.write().unwrap() = names;
+
+        Ok(())
+    }
+
+    /// Add the name of a user who just signed up, before the next reload
+    pub fn insert(&self, name: &str) {
+        self.names.write().unwrap().push(name.to_string());
+    }
+
+    /// Drop the name of a user who was just deleted, before the next reload
+    pub fn remove(&self, name: &str) {
+        self.names.write().unwrap().retain(|other| other != name);
+    }
+
+    /// Whether the name looks like one of `protected_names` or the name of a
+    /// user other than the one named `own_name`
+    pub fn is_reserved(
+        &self,
+        name: &str,
+        own_name: Option<&str>,
+        protected_names: &[String],
+    ) -> bool {
+        let names = self.names.read().unwrap();
+
+        names
+            .iter()
+            .filter(|registered| Some(registered.as_str()) != own_name)
+            .chain(protected_names.iter())
+            .any(|reserved| is_confusable(reserved, name))
+    }
 }
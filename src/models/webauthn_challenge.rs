@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    error::Error,
+    options::IndexOptions,
+    Database, IndexModel,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::constants::auth::WEBAUTHN_CHALLENGE_LIFETIME_IN_SECONDS;
+
+const COLLECTION_NAME: &str = "webauthn_challenges";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum CeremonyKind {
+    Registration,
+    Authentication,
+}
+
+/// State of a passkey ceremony between its start and its end
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebauthnChallenge {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+
+    /// User registering or signing in with a passkey
+    #[serde(rename = "userId")]
+    pub user_id: ObjectId,
+
+    pub kind: CeremonyKind,
+
+    /// Serialized ceremony state of `webauthn_rs`
+    pub state: String,
+
+    #[serde(
+        rename = "expiresAt",
+        with = "bson::serde_helpers::chrono_datetime_as_bson_datetime"
+    )]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl WebauthnChallenge {
+    pub async fn create_indexes(db: &Database) -> Result<(), Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let indexes = vec![IndexModel::builder()
+            .keys(doc! {"expiresAt": 1})
+            .options(
+                IndexOptions::builder()
+                    .expire_after(Duration::from_secs(0))
+                    .build(),
+            )
+            .build()];
+
+        collection.create_indexes(indexes).await?;
+
+        Ok(())
+    }
+
+    /// Store the state of a ceremony and return its id
+    pub async fn create<T: Serialize>(
+        db: &Database,
+        user_id: ObjectId,
+        kind: CeremonyKind,
+        state: &T,
+    ) -> Result<ObjectId, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let challenge = Self {
+            id: None,
+            user_id,
+            kind,
+            state: serde_json::to_string(state).map_err(|e| Error::custom(e.to_string()))?,
+            expires_at: Utc::now()
+                + chrono::Duration::seconds(WEBAUTHN_CHALLENGE_LIFETIME_IN_SECONDS),
+        };
+
+        let result = collection.insert_one(challenge).await?;
+
+        result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| Error::custom("Failed to create WebAuthn challenge"))
+    }
+
+    /// Remove the ceremony and return its user and state, so it can only finish once
+    pub async fn take<T: for<'de> Deserialize<'de>>(
+        db: &Database,
+        id: &str,
+        kind: CeremonyKind,
+    ) -> Result<Option<(ObjectId, T)>, Error> {
+        let collection = db.collection::<Self>(COLLECTION_NAME);
+        let object_id = match ObjectId::parse_str(id) {
+            Ok(object_id) => object_id,
+            Err(_) => return Ok(None),
+        };
+        let challenge = collection
+            .find_one_and_delete(doc! {
+                "_id": object_id,
+                "kind": bson::to_bson(&kind)?,
+                "expiresAt": {"$gt": bson::DateTime::from_chrono(Utc::now())},
+            })
+            .await?;
+
+        match challenge {
+            Some(challenge) => {
+                let state = serde_json::from_str(&challenge.state)
+                    .map_err(|e| Error::custom(e.to_string()))?;
+
+                Ok(Some((challenge.user_id, state)))
+            }
+            None => Ok(None),
+        }
+    }
+}
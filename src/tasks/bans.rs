@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use tokio::time::interval;
+
+use crate::env::state::AppState;
+
+const REFRESH_INTERVAL_IN_SECONDS: u64 = 60;
+
+/// Keep the in-memory ban list in sync with the database
+///
+/// Bans changed through the API are reloaded immediately, this catches
+/// changes made by other instances or directly in the database.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(REFRESH_INTERVAL_IN_SECONDS));
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = state.bans.reload(&state.db).await {
+                log::error!("[Bans] Failed to reload ban list: {}", e);
+            }
+        }
+    });
+}
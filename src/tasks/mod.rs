@@ -0,0 +1,3 @@
+pub mod bans;
+pub mod purge;
+pub mod user_names;
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::time::interval;
+
+use crate::{
+    constants::time::{ONE_DAY_IN_SECONDS, ONE_HOUR_IN_SECONDS},
+    env::state::AppState,
+    models::comment::Comment,
+};
+
+/// Periodically remove commenter fingerprints older than the retention period
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(ONE_HOUR_IN_SECONDS as u64));
+
+        loop {
+            ticker.tick().await;
+
+            let before = Utc::now()
+                - chrono::Duration::seconds(
+                    state.comment_fingerprint_retention_days * ONE_DAY_IN_SECONDS,
+                );
+
+            match Comment::purge_moderation(&state.db, before).await {
+                Ok(0) => {}
+                Ok(count) => log::info!("[Purge] Removed moderation data from {} comments", count),
+                Err(e) => log::error!("[Purge] Failed to purge moderation data: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use tokio::time::interval;
+
+use crate::env::state::AppState;
+
+const REFRESH_INTERVAL_IN_SECONDS: u64 = 60;
+
+/// Keep the in-memory list of user names in sync with the database
+///
+/// Names of users signing up are added immediately, this catches users
+/// created by other instances or deleted since.
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(REFRESH_INTERVAL_IN_SECONDS));
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(e) = state.user_names.reload(&state.db).await {
+                log::error!("[Users] Failed to reload user names: {}", e);
+            }
+        }
+    });
+}
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use axum::http::HeaderMap;
+
+    use crate::utils::client::ClientInfo;
+
+    fn headers(forwarded_for: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", forwarded_for.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn should_ignore_hops_added_by_the_client() {
+        let peer = "10.0.0.2".parse::<IpAddr>().ok();
+        let headers = headers("1.1.1.1, 203.0.113.7, 10.0.0.1");
+
+        let behind_one = ClientInfo::from_request(&headers, peer, 1);
+        let behind_two = ClientInfo::from_request(&headers, peer, 2);
+
+        assert_eq!(behind_one.ip.as_deref(), Some("10.0.0.1"));
+        assert_eq!(behind_two.ip.as_deref(), Some("203.0.113.7"));
+    }
+
+    #[test]
+    fn should_use_socket_address_without_proxies() {
+        let peer = "198.51.100.4".parse::<IpAddr>().ok();
+        let client = ClientInfo::from_request(&headers("1.1.1.1"), peer, 0);
+
+        assert_eq!(client.ip.as_deref(), Some("198.51.100.4"));
+    }
+
+    #[test]
+    fn should_reject_invalid_addresses() {
+        let client = ClientInfo::from_request(&headers("not an ip"), None, 1);
+
+        assert!(client.ip.is_none());
+    }
+}
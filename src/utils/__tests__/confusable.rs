@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::confusable::is_confusable;
+
+    #[test]
+    fn should_match_latin_look_alikes() {
+        assert!(is_confusable("marshall", "Marshall"));
+        assert!(is_confusable("marshall", "marshaIl"));
+        assert!(is_confusable("marshall", "mаrshall")); // Cyrillic а
+        assert!(is_confusable("marshall", "ｍａｒｓｈａｌｌ"));
+        assert!(is_confusable("marshall", "mar\u{200B}shall"));
+        assert!(!is_confusable("marshall", "marsha"));
+    }
+
+    #[test]
+    fn should_match_hangul_look_alikes() {
+        assert!(is_confusable("마샬", "마샬"));
+        assert!(is_confusable("마샬", "ㅁㅏ샬"));
+        assert!(is_confusable("마샬", "마\u{3164}샬"));
+        assert!(is_confusable("마샬", "\u{1106}\u{1161}샬"));
+        assert!(!is_confusable("마샬", "마살"));
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::{
+        constants::time::ONE_DAY_IN_SECONDS,
+        utils::form_token::{issue, sign, verify},
+    };
+
+    #[test]
+    fn should_return_issue_time_of_valid_token() {
+        let issued_at = Utc::now().timestamp() - 10;
+        let token = sign(issued_at, "secret");
+
+        assert_eq!(verify(&token, "secret"), Some(issued_at));
+        assert!(verify(&issue("secret"), "secret").is_some());
+    }
+
+    #[test]
+    fn should_reject_tampered_token() {
+        let token = issue("secret");
+        let (issued_at, signature) = token.split_once('.').unwrap();
+        let backdated = format!("{}.{}", issued_at.parse::<i64>().unwrap() - 60, signature);
+
+        assert!(verify(&token, "other secret").is_none());
+        assert!(verify(&backdated, "secret").is_none());
+        assert!(verify(issued_at, "secret").is_none());
+    }
+
+    #[test]
+    fn should_reject_expired_token() {
+        let token = sign(Utc::now().timestamp() - ONE_DAY_IN_SECONDS - 1, "secret");
+
+        assert!(verify(&token, "secret").is_none());
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::ip::{cidr_contains, is_public_ip, is_same_ip, parse_cidr};
+
+    #[test]
+    fn should_parse_cidr() {
+        assert!(parse_cidr("10.0.0.0/8").is_some());
+        assert!(parse_cidr("2001:db8::/32").is_some());
+        assert!(parse_cidr("10.0.0.0/33").is_none());
+        assert!(parse_cidr("10.0.0.0").is_none());
+    }
+
+    #[test]
+    fn should_match_ipv4_range() {
+        assert!(cidr_contains("192.168.0.0/16", "192.168.12.34"));
+        assert!(!cidr_contains("192.168.0.0/16", "192.169.0.1"));
+        assert!(cidr_contains("0.0.0.0/0", "8.8.8.8"));
+        assert!(cidr_contains("192.168.0.0/16", "::ffff:192.168.1.1"));
+    }
+
+    #[test]
+    fn should_match_ipv6_range() {
+        assert!(cidr_contains("2001:db8::/32", "2001:db8:1::1"));
+        assert!(!cidr_contains("2001:db8::/32", "2001:db9::1"));
+        assert!(!cidr_contains("2001:db8::/32", "192.168.0.1"));
+    }
+
+    #[test]
+    fn should_compare_ip() {
+        assert!(is_same_ip("::ffff:10.0.0.1", "10.0.0.1"));
+        assert!(!is_same_ip("10.0.0.1", "10.0.0.2"));
+    }
+
+    #[test]
+    fn should_tell_public_ip() {
+        assert!(is_public_ip("8.8.8.8"));
+        assert!(is_public_ip("2606:4700::1111"));
+
+        assert!(!is_public_ip("127.0.0.1"));
+        assert!(!is_public_ip("10.1.2.3"));
+        assert!(!is_public_ip("172.16.0.1"));
+        assert!(!is_public_ip("192.168.1.1"));
+        assert!(!is_public_ip("169.254.169.254"));
+        assert!(!is_public_ip("0.0.0.0"));
+        assert!(!is_public_ip("::1"));
+        assert!(!is_public_ip("fd00::1"));
+        assert!(!is_public_ip("::ffff:127.0.0.1"));
+        assert!(!is_public_ip("not an ip"));
+    }
+}
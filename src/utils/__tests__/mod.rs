@@ -0,0 +1,9 @@
+mod client;
+mod confusable;
+mod form_token;
+mod ip;
+mod password;
+mod pattern;
+mod text;
+mod totp;
+mod user_agent;
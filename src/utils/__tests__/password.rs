@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::password::PasswordPolicy;
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 8,
+            min_character_classes: 2,
+            reject_common: true,
+        }
+    }
+
+    #[test]
+    fn should_accept_strong_password() {
+        assert!(policy().check("correct horse battery", "marshall").is_ok());
+        assert!(policy().check("비밀번호는길게2024", "marshall").is_ok());
+    }
+
+    #[test]
+    fn should_reject_weak_password() {
+        assert!(policy().check("short1", "marshall").is_err());
+        assert!(policy().check("onlyletters", "marshall").is_err());
+        assert!(policy().check("Password1", "marshall").is_err());
+        assert!(policy().check("Marshall2024", "marshall").is_err());
+        assert!(policy().check(&"a1".repeat(40), "marshall").is_err());
+    }
+}
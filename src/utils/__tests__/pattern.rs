@@ -0,0 +1,20 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::pattern::{escape_regex, matches_wildcard};
+
+    #[test]
+    fn should_match_wildcard() {
+        assert!(matches_wildcard("*@spam.example", "bot@SPAM.example"));
+        assert!(matches_wildcard("bot*", "botnet"));
+        assert!(matches_wildcard("a*b*c", "axxbyyc"));
+        assert!(!matches_wildcard("a*b*c", "axxbyy"));
+        assert!(!matches_wildcard("exact", "exactly"));
+    }
+
+    #[test]
+    fn should_escape_regex() {
+        assert_eq!(escape_regex("a.b*c"), "a\\.b\\*c");
+        assert_eq!(escape_regex("(x|y)"), "\\(x\\|y\\)");
+        assert_eq!(escape_regex("plain"), "plain");
+    }
+}
@@ -0,0 +1,22 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::text::normalize_body;
+
+    #[test]
+    fn should_ignore_case_and_whitespace() {
+        assert_eq!(normalize_body("  Hello,\n\tWorld!  "), "hello, world!");
+        assert_eq!(
+            normalize_body("Hello, world!"),
+            normalize_body("HELLO,   WORLD!")
+        );
+    }
+
+    #[test]
+    fn should_keep_other_differences() {
+        assert_ne!(
+            normalize_body("Hello, world!"),
+            normalize_body("Hello world")
+        );
+        assert_eq!(normalize_body(" \n "), "");
+    }
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use data_encoding::BASE32_NOPAD;
+
+    use crate::utils::totp::{code_at, normalize_recovery_code, verify};
+
+    const SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn should_match_rfc_6238_vectors() {
+        // Last 6 digits of the SHA-1 vectors of RFC 6238
+        assert_eq!(code_at(SECRET, 59 / 30), "287082");
+        assert_eq!(code_at(SECRET, 1111111109 / 30), "081804");
+        assert_eq!(code_at(SECRET, 1234567890 / 30), "005924");
+        assert_eq!(code_at(SECRET, 2000000000 / 30), "279037");
+    }
+
+    #[test]
+    fn should_verify_code_once() {
+        let secret = BASE32_NOPAD.encode(SECRET);
+
+        assert_eq!(verify(&secret, "287082", 59, 0), Some(1));
+        assert_eq!(verify(&secret, "287082", 89, 0), Some(1));
+        assert_eq!(verify(&secret, "287082", 59, 1), None);
+        assert_eq!(verify(&secret, "000000", 59, 0), None);
+    }
+
+    #[test]
+    fn should_normalize_recovery_code() {
+        assert_eq!(normalize_recovery_code(" ABCD-efgh "), "abcdefgh");
+    }
+}
@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests {
+    use crate::utils::user_agent::describe;
+
+    #[test]
+    fn should_describe_common_browsers() {
+        assert_eq!(
+            describe("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36 Edg/124.0.0.0"),
+            "Edge on Windows"
+        );
+        assert_eq!(
+            describe("Mozilla/5.0 (iPhone; CPU iPhone OS 17_4 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Mobile/15E148 Safari/604.1"),
+            "Safari on iOS"
+        );
+        assert_eq!(
+            describe("Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0"),
+            "Firefox on Linux"
+        );
+        assert_eq!(describe("curl/8.5.0"), "Unknown device");
+    }
+}
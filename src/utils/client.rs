@@ -0,0 +1,92 @@
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::{request::Parts, Extensions, HeaderMap},
+};
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use crate::env::state::AppState;
+
+/// Information about the client that sent the request
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    /// Identify the client of a request, from the socket address stored by
+    /// `into_make_service_with_connect_info`
+    pub fn from_http(headers: &HeaderMap, extensions: &Extensions, trusted_proxies: usize) -> Self {
+        let peer = extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(address)| address.ip());
+
+        Self::from_request(headers, peer, trusted_proxies)
+    }
+
+    /// Behind `trusted_proxies` reverse proxies, the client is the address
+    /// the outermost of them saw. Each proxy appends the address it got the
+    /// request from to `x-forwarded-for`, so that's the `trusted_proxies`-th
+    /// entry from the right, the ones on its left being whatever the client
+    /// sent. Without proxies, the socket address is used.
+    pub fn from_request(headers: &HeaderMap, peer: Option<IpAddr>, trusted_proxies: usize) -> Self {
+        let forwarded_ip = || {
+            let hops = headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(|hop| hop.trim())
+                .filter(|hop| !hop.is_empty())
+                .collect::<Vec<&str>>();
+
+            match hops.len().checked_sub(trusted_proxies) {
+                Some(index) => hops.get(index).copied(),
+                // Every hop was added by a trusted proxy
+                None => hops.first().copied(),
+            }
+            .or_else(|| {
+                headers
+                    .get("x-real-ip")
+                    .and_then(|value| value.to_str().ok())
+            })
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+        };
+        let ip = match trusted_proxies {
+            0 => peer,
+            _ => forwarded_ip().or(peer),
+        };
+        let user_agent = headers
+            .get("user-agent")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        Self {
+            ip: ip.map(|ip| ip.to_string()),
+            user_agent,
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = AppState::from_ref(state);
+
+        Ok(Self::from_http(
+            &parts.headers,
+            &parts.extensions,
+            state.trusted_proxies,
+        ))
+    }
+}
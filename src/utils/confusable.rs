@@ -0,0 +1,64 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_security::confusable_detection::skeleton;
+
+/// Characters that render as nothing and can be used to make a name look
+/// identical to another one
+const INVISIBLE_CHARS: &[char] = &[
+    '\u{00AD}', // Soft hyphen
+    '\u{034F}', // Combining grapheme joiner
+    '\u{115F}', // Hangul choseong filler
+    '\u{1160}', // Hangul jungseong filler
+    '\u{180E}', // Mongolian vowel separator
+    '\u{200B}', // Zero width space
+    '\u{200C}', // Zero width non-joiner
+    '\u{200D}', // Zero width joiner
+    '\u{200E}', // Left-to-right mark
+    '\u{200F}', // Right-to-left mark
+    '\u{202A}', // Left-to-right embedding
+    '\u{202B}', // Right-to-left embedding
+    '\u{202C}', // Pop directional formatting
+    '\u{202D}', // Left-to-right override
+    '\u{202E}', // Right-to-left override
+    '\u{2060}', // Word joiner
+    '\u{2061}', // Function application
+    '\u{2062}', // Invisible times
+    '\u{2063}', // Invisible separator
+    '\u{2064}', // Invisible plus
+    '\u{3164}', // Hangul filler
+    '\u{FEFF}', // Zero width no-break space
+    '\u{FFA0}', // Halfwidth Hangul filler
+];
+
+/// Whether the character renders as nothing
+pub fn is_invisible(c: char) -> bool {
+    INVISIBLE_CHARS.contains(&c)
+}
+
+/// Keys identifying how a name looks
+///
+/// The name is NFKC normalized, so compatibility jamo and full-width forms
+/// collapse into their usual forms, stripped of whitespace and invisible
+/// characters, and reduced to its UTS #39 confusable skeleton. The skeleton is
+/// computed both with and without lowercasing first, as `I` and `l` are only
+/// confusable before case folding.
+fn confusable_keys(name: &str) -> [String; 2] {
+    let normalized = name
+        .nfkc()
+        .filter(|c| !c.is_whitespace() && !is_invisible(*c))
+        .collect::<String>();
+
+    [
+        skeleton(&normalized).collect::<String>().to_lowercase(),
+        skeleton(&normalized.to_lowercase())
+            .collect::<String>()
+            .to_lowercase(),
+    ]
+}
+
+/// Whether two names could be mistaken for each other
+pub fn is_confusable(a: &str, b: &str) -> bool {
+    let a = confusable_keys(a);
+    let b = confusable_keys(b);
+
+    a.iter().any(|key| !key.is_empty() && b.contains(key))
+}
@@ -1,4 +1,9 @@
+use std::sync::LazyLock;
+
 use bcrypt::{hash, verify, BcryptError};
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
 
 /// The number of rounds to use for the bcrypt hash.
 const SALT_ROUNDS: u32 = 10;
@@ -16,3 +21,42 @@ pub fn hash_password(password: &str) -> Result<String, BcryptError> {
 pub fn verify_password(password: &str, hashed_password: &str) -> Result<bool, BcryptError> {
     verify(password, hashed_password)
 }
+
+/// Hash checked against when there is no user to check, so the check takes
+/// as long as a real one
+static DUMMY_PASSWORD_HASH: LazyLock<String> =
+    LazyLock::new(|| hash(generate_token(), SALT_ROUNDS).unwrap_or_default());
+
+/// Verify a password against a hash nobody knows the password of
+///
+/// Always fails, but takes as long as `verify_password`.
+pub fn verify_dummy_password(password: &str) -> bool {
+    let _ = verify(password, &DUMMY_PASSWORD_HASH);
+
+    false
+}
+
+/// Hash an IP address with a server-side salt.
+///
+/// The result is stable for the same salt, so it can be used to group
+/// requests from the same address without storing the address itself.
+pub fn hash_ip(ip: &str, salt: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(salt.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(ip.as_bytes());
+
+    HEXLOWER.encode(&mac.finalize().into_bytes())
+}
+
+/// Generate a random, URL-safe token
+pub fn generate_token() -> String {
+    BASE64URL_NOPAD.encode(&rand::random::<[u8; 32]>())
+}
+
+/// Hash a random token for storage
+///
+/// Tokens generated by `generate_token` have enough entropy, so a fast hash is
+/// enough to keep them useless if the database leaks.
+pub fn hash_token(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
+}
@@ -0,0 +1,50 @@
+use chrono::Utc;
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::constants::time::ONE_DAY_IN_SECONDS;
+
+/// How long a rendered comment form can be submitted
+const FORM_TOKEN_LIFETIME_IN_SECONDS: i64 = ONE_DAY_IN_SECONDS;
+
+/// Issue a token carrying the time the comment form was rendered.
+///
+/// The token has the form `<unix timestamp>.<hex signature>`.
+pub fn issue(secret_key: &str) -> String {
+    sign(Utc::now().timestamp(), secret_key)
+}
+
+/// Token of a form rendered at `issued_at`
+pub fn sign(issued_at: i64, secret_key: &str) -> String {
+    let signature = new_mac(secret_key, issued_at).finalize().into_bytes();
+
+    format!("{}.{}", issued_at, HEXLOWER.encode(&signature))
+}
+
+/// Verify a form token and return the time it was issued at.
+///
+/// Returns `None` if the token is malformed, expired or the signature
+/// doesn't match.
+pub fn verify(token: &str, secret_key: &str) -> Option<i64> {
+    let (issued_at, signature) = token.split_once('.')?;
+    let issued_at = issued_at.parse::<i64>().ok()?;
+    let signature = HEXLOWER.decode(signature.as_bytes()).ok()?;
+
+    if Utc::now().timestamp() - issued_at > FORM_TOKEN_LIFETIME_IN_SECONDS {
+        return None;
+    }
+
+    new_mac(secret_key, issued_at)
+        .verify_slice(&signature)
+        .ok()
+        .map(|_| issued_at)
+}
+
+fn new_mac(secret_key: &str, issued_at: i64) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+        .expect("HMAC can take key of any size");
+    mac.update(b"comment-form:");
+    mac.update(issued_at.to_string().as_bytes());
+    mac
+}
@@ -0,0 +1,85 @@
+use std::net::IpAddr;
+
+/// Parse a CIDR range such as `192.168.0.0/16` or `2001:db8::/32`.
+pub fn parse_cidr(cidr: &str) -> Option<(IpAddr, u8)> {
+    let (address, prefix) = cidr.trim().split_once('/')?;
+    let address = address.parse::<IpAddr>().ok()?;
+    let prefix = prefix.parse::<u8>().ok()?;
+    let max_prefix = match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+
+    if prefix > max_prefix {
+        return None;
+    }
+
+    Some((address, prefix))
+}
+
+/// Check whether `ip` belongs to the CIDR range.
+///
+/// IPv4-mapped IPv6 addresses are compared as IPv4.
+pub fn cidr_contains(cidr: &str, ip: &str) -> bool {
+    let (network, prefix) = match parse_cidr(cidr) {
+        Some(range) => range,
+        None => return false,
+    };
+    let ip = match ip.trim().parse::<IpAddr>() {
+        Ok(ip) => ip.to_canonical(),
+        Err(_) => return false,
+    };
+
+    match (network.to_canonical(), ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(network) & mask == u32::from(ip) & mask
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(network) & mask == u128::from(ip) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Ranges of addresses that aren't reachable on the internet, such as
+/// loopback, private, link-local and documentation ranges
+const NON_PUBLIC_RANGES: &[&str] = &[
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "100.64.0.0/10",
+    "127.0.0.0/8",
+    "169.254.0.0/16",
+    "172.16.0.0/12",
+    "192.0.0.0/24",
+    "192.0.2.0/24",
+    "192.168.0.0/16",
+    "198.18.0.0/15",
+    "198.51.100.0/24",
+    "203.0.113.0/24",
+    "224.0.0.0/3",
+    "::/127",
+    "64:ff9b::/96",
+    "2001:db8::/32",
+    "fc00::/7",
+    "fe80::/10",
+    "ff00::/8",
+];
+
+/// Check whether `ip` is a public address, which the server may connect to
+/// on behalf of users.
+pub fn is_public_ip(ip: &str) -> bool {
+    ip.trim().parse::<IpAddr>().is_ok()
+        && !NON_PUBLIC_RANGES
+            .iter()
+            .any(|range| cidr_contains(range, ip))
+}
+
+/// Compare two IP addresses, ignoring formatting differences.
+pub fn is_same_ip(a: &str, b: &str) -> bool {
+    match (a.trim().parse::<IpAddr>(), b.trim().parse::<IpAddr>()) {
+        (Ok(a), Ok(b)) => a.to_canonical() == b.to_canonical(),
+        _ => false,
+    }
+}
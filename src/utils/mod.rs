@@ -1,4 +1,17 @@
+mod __tests__;
+
+pub mod client;
+pub mod confusable;
 pub mod encryption;
+pub mod form_token;
+pub mod ip;
 pub mod log;
+pub mod mailer;
+pub mod password;
+pub mod pattern;
+pub mod text;
+pub mod totp;
+pub mod tripcode;
+pub mod user_agent;
 pub mod validator;
 pub mod webhook;
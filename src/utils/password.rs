@@ -0,0 +1,87 @@
+/// Passwords too common to be accepted, one per line
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+
+/// bcrypt ignores everything past the 72nd byte
+const MAX_PASSWORD_BYTES: usize = 72;
+
+/// Names shorter than this aren't looked for in passwords
+const MIN_NAME_LENGTH_TO_CHECK: usize = 3;
+
+/// Rules passwords of new accounts must follow
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// How many of lowercase letters, uppercase letters, digits and other
+    /// characters the password must mix
+    pub min_character_classes: usize,
+    /// Whether to reject passwords from the bundled list of common passwords
+    pub reject_common: bool,
+}
+
+impl PasswordPolicy {
+    /// Check the password of the user named `name`, returning why it was rejected
+    pub fn check(&self, password: &str, name: &str) -> Result<(), String> {
+        if password.chars().count() < self.min_length {
+            return Err(format!(
+                "Password must be at least {} characters",
+                self.min_length
+            ));
+        }
+
+        if password.len() > MAX_PASSWORD_BYTES {
+            return Err(format!(
+                "Password must be at most {} bytes",
+                MAX_PASSWORD_BYTES
+            ));
+        }
+
+        if character_classes(password) < self.min_character_classes {
+            return Err(format!(
+                "Password must mix at least {} of lowercase letters, uppercase letters, digits and symbols",
+                self.min_character_classes
+            ));
+        }
+
+        let lowercase_password = password.to_lowercase();
+
+        if self.reject_common && is_common(&lowercase_password) {
+            return Err("Password is too common".to_string());
+        }
+
+        let lowercase_name = name.to_lowercase();
+
+        if lowercase_name.chars().count() >= MIN_NAME_LENGTH_TO_CHECK
+            && lowercase_password.contains(&lowercase_name)
+        {
+            return Err("Password must not contain your name".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+fn character_classes(password: &str) -> usize {
+    let mut classes = [false; 4];
+
+    for c in password.chars() {
+        let class = if c.is_lowercase() {
+            0
+        } else if c.is_uppercase() {
+            1
+        } else if c.is_numeric() {
+            2
+        } else {
+            3
+        };
+
+        classes[class] = true;
+    }
+
+    classes.iter().filter(|&&has_class| has_class).count()
+}
+
+fn is_common(lowercase_password: &str) -> bool {
+    COMMON_PASSWORDS
+        .lines()
+        .any(|common| common == lowercase_password)
+}
@@ -0,0 +1,9 @@
+/// Normalize a comment body for comparison
+///
+/// Case and whitespace differences are ignored.
+pub fn normalize_body(body: &str) -> String {
+    body.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
@@ -0,0 +1,102 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+/// Number of digits of a code
+const DIGITS: u32 = 6;
+
+/// How long a code is valid for
+pub const STEP_IN_SECONDS: i64 = 30;
+
+/// Codes of the steps right before and after the current one are accepted,
+/// as the clocks of the server and the authenticator can drift apart
+const ALLOWED_SKEW_STEPS: i64 = 1;
+
+/// Number of recovery codes generated on enrollment
+pub const RECOVERY_CODE_COUNT: usize = 10;
+
+/// Generate a random secret, encoded in base32 as authenticators expect
+pub fn generate_secret() -> String {
+    BASE32_NOPAD.encode(&rand::random::<[u8; 20]>())
+}
+
+/// Code of the given time step, as defined by RFC 6238 with HMAC-SHA1
+pub fn code_at(secret: &[u8], step: i64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC can take key of any size");
+    mac.update(&step.to_be_bytes());
+
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    format!(
+        "{:0width$}",
+        binary % 10_u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// Check a code at the given Unix timestamp and return its time step
+///
+/// Steps up to `last_used_step` are rejected, so a code can't be used twice.
+pub fn verify(secret: &str, code: &str, now: i64, last_used_step: i64) -> Option<i64> {
+    let secret = BASE32_NOPAD.decode(secret.as_bytes()).ok()?;
+    let code = code.trim();
+    let current_step = now / STEP_IN_SECONDS;
+
+    (current_step - ALLOWED_SKEW_STEPS..=current_step + ALLOWED_SKEW_STEPS)
+        .filter(|&step| step > last_used_step)
+        .find(|&step| code_at(&secret, step) == code)
+}
+
+/// `otpauth://` URI to show as a QR code to authenticators
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        DIGITS,
+        STEP_IN_SECONDS
+    )
+}
+
+/// Generate codes that can each be used once instead of a TOTP code
+///
+/// Codes have the form `xxxx-xxxx`.
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let code = BASE32_NOPAD
+                .encode(&rand::random::<[u8; 5]>())
+                .to_lowercase();
+            format!("{}-{}", &code[..4], &code[4..])
+        })
+        .collect()
+}
+
+/// Normalize a recovery code typed by the user before hashing it
+pub fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,21 @@
+use data_encoding::BASE64URL_NOPAD;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Length of the tripcode shown next to the commenter's name
+const TRIPCODE_LENGTH: usize = 10;
+
+/// Derive a tripcode from a commenter's secret
+///
+/// The tripcode is stable for the same secret and server key, and can't be
+/// reversed to get the secret without the server key.
+pub fn derive(secret: &str, key: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(b"tripcode:");
+    mac.update(secret.as_bytes());
+
+    let mut tripcode = BASE64URL_NOPAD.encode(&mac.finalize().into_bytes());
+    tripcode.truncate(TRIPCODE_LENGTH);
+    tripcode
+}